@@ -1,12 +1,56 @@
+use std::{path::PathBuf, time::Duration};
+
 use super::*;
+use crate::{
+    command::{HexPart, LineEnding},
+    encoding::Charset,
+    logger::LogOptions,
+    modem::{LineStep, ResetSequence},
+    widgets::{AnsiMode, HighlightRule, NewlineMode, TimestampMode, ViewMode},
+};
 
 #[derive(Debug, Clone)]
 pub enum Action {
     None,
     Quit,
     SwitchMode(Mode),   // 切换当前焦点模式
-    SelectPort(String), // 选中了某个串口
+    SelectPort(String), // 选中了某个串口（名称、列表项、VID:PID 或序列号）
     SelectRate(String), // 选中了某个波特率
+    SelectDataBits(String),
+    SelectParity(String),
+    SelectStopBits(String),
+    SelectFlowControl(String),
     Open,
-    Error(String),
+    Close,                               // 关闭串口
+    Reopen,                              // 关闭后用当前参数重新打开（换串口或恢复出错的连接）
+    SetLines(Vec<LineStep>),             // 设置 DTR / RTS（可含等待）
+    SendBreak(Duration),                 // 发送一段时间的 BREAK
+    RunReset(String),                    // 执行命名的复位序列
+    DefineReset(ResetSequence),          // 定义（或替换同名的）复位序列
+    ListResets,                          // 在接收区列出复位序列
+    SetReconnect(bool),                  // 断线后是否自动重新连接
+    RefreshPorts,                        // 立即重新枚举串口
+    Send(String),                        // 发送一行文本（行结束符由 App 追加）
+    SendHex(Vec<HexPart>),               // hex 命令：字节原样发送，引号中的文本按字符集编码
+    SendRaw(Vec<u8>),                    // 终端模式的按键，发送后不回显
+    SetLineEnding(LineEnding),           // 设置发送行结束符
+    SetCharset(Charset),                 // 设置收发文本的字符集
+    SetNewline(NewlineMode),             // 设置接收数据的断行方式
+    SetView(ViewMode),                   // 切换接收区显示方式
+    SetTimestamp(Option<TimestampMode>), // 设置行首时间戳，None 表示依次切换
+    SetAnsi(Option<AnsiMode>),           // 设置 ANSI 转义序列的显示方式，None 表示切换
+    StartLog(LogOptions),                // 开始记录会话日志
+    StopLog,
+    SetScrollback(usize), // 设置接收区最多保留的行数
+    Search(String),       // 在接收区中搜索，空字符串清除搜索
+    SetSearchRegex(bool), // 搜索按正则 / 纯文本匹配
+    AddHighlight(HighlightRule),
+    ListHighlights,                 // 在接收区列出高亮规则
+    RemoveHighlight(Option<usize>), // 删除第 n 条高亮规则，None 表示全部删除
+    LoadHighlights(PathBuf),        // 从配置文件追加高亮规则
+    SetFilter(String, bool),        // 只显示匹配正则的行，true 表示反向过滤
+    ClearFilter,
+    Error(String),         // 弹出错误对话框，并记入消息记录
+    Notify(Level, String), // 显示几秒后消失的提示消息，并记入消息记录
+    ShowMessages,          // 打开消息记录
 }
@@ -1,39 +1,171 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    encoding::Charset,
+    logger::LogOptions,
+    modem::{
+        ControlLine, LineStep, ResetSequence, parse_break_command, parse_line_command, parse_steps,
+    },
+    widgets::{AnsiMode, HighlightRule, NewlineMode, TimestampMode, ViewMode},
+};
+
 pub enum Command {
     ModeToUartChoice,
     ModeToRateChoice,
+    ModeToSendInput,
+    ModeToTerminal,
+    ModeToDataBitsChoice,
+    ModeToParityChoice,
+    ModeToStopBitsChoice,
+    ModeToFlowControlChoice,
+    SetBaudRate(String),
+    SetDataBits(String),
+    SetParity(String),
+    SetStopBits(String),
+    SetFlowControl(String),
     Open,
+    Close,
+    Reopen,
+    RefreshPorts,
+    SetReconnect(bool),
+    SetLines(Vec<LineStep>), // dtr / rts 命令
+    SendBreak(Duration),
+    RunReset(String),
+    DefineReset(ResetSequence),
+    ListResets,
+    SelectPort(String), // 按名称、VID:PID 或序列号选择串口
+    Send(String),
+    SendHex(Vec<HexPart>),
+    SetLineEnding(LineEnding),
+    SetCharset(Charset),
+    SetNewline(NewlineMode),
+    SetView(ViewMode),
+    SetTimestamp(Option<TimestampMode>), // None 表示依次切换
+    SetAnsi(Option<AnsiMode>),           // None 表示在解析和原样显示之间切换
+    StartLog(LogOptions),
+    StopLog,
+    SetScrollback(usize),
+    SetSearchRegex(bool), // 搜索按正则 / 纯文本匹配
+    ClearSearch,
+    AddHighlight(HighlightRule),
+    ListHighlights,
+    RemoveHighlight(Option<usize>), // None 表示全部删除
+    LoadHighlights(PathBuf),
+    SetFilter { pattern: String, invert: bool },
+    ClearFilter,
+    ShowMessages,
     Quit,
 }
 
+// 发送时追加在每行末尾的行结束符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    None,
+    Cr,
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            LineEnding::None => b"",
+            LineEnding::Cr => b"\r",
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(LineEnding::None),
+            "cr" => Ok(LineEnding::Cr),
+            "lf" => Ok(LineEnding::Lf),
+            "crlf" => Ok(LineEnding::CrLf),
+            _ => Err(format!("Unknown line ending: {s} (none/cr/lf/crlf)")),
+        }
+    }
+}
+
+// hex 命令的一段：直接给出的字节（含字符串中的 \x 转义），或引号中的文本。
+// 文本在发送时才按当前字符集编码
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HexPart {
+    Bytes(Vec<u8>),
+    Text(String),
+}
+
+// 把 hex 命令的各段拼成要发送的字节
+pub fn encode_hex_parts(parts: &[HexPart], charset: Charset) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for part in parts {
+        match part {
+            HexPart::Bytes(b) => bytes.extend_from_slice(b),
+            HexPart::Text(text) => bytes.extend(charset.encode(text)?),
+        }
+    }
+    Ok(bytes)
+}
+
 pub struct ParsedCommand {
-    pub name: String,      // 命令名，如 "help"
-    pub args: Vec<String>, // 参数列表，如 ["file.txt", "--verbose"]
+    pub name: String,       // 命令名，如 "help"
+    pub args: Vec<String>,  // 参数列表，如 ["file.txt", "--verbose"]
+    pub tokens: Vec<Token>, // 与 args 一一对应，保留原文和位置
+    pub rest: String,       // 命令名和一个分隔空格之后的原始输入，send 原样发送
+}
+
+// 一个词法单元：处理后的内容 + 原始输入片段 + 起始列
+pub struct Token {
+    pub text: String,  // 去掉引号、处理过 \" \\ 转义后的内容
+    pub raw: String,   // 原始输入片段（含引号），hex 等命令需要自己处理转义
+    pub column: usize, // 起始列（按字符计，从 1 开始），用于报错定位
 }
 
-fn tokenize(input: &str) -> Result<Vec<String>, String> {
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
     let mut tokens = Vec::new();
     let mut current = String::new();
+    let mut raw = String::new();
+    let mut column = 0;
     let mut in_quotes = false;
-    let mut chars = input.chars().peekable();
+    let mut chars = input.chars().enumerate().peekable();
 
-    while let Some(ch) = chars.next() {
-        match ch {
-            '"' if !in_quotes => in_quotes = true,
-            '"' if in_quotes => in_quotes = false,
-            ' ' if !in_quotes => {
-                if !current.is_empty() {
-                    tokens.push(std::mem::take(&mut current));
-                }
+    while let Some((i, ch)) = chars.next() {
+        if ch == ' ' && !in_quotes {
+            if !raw.is_empty() {
+                tokens.push(Token {
+                    text: std::mem::take(&mut current),
+                    raw: std::mem::take(&mut raw),
+                    column,
+                });
             }
-            '\\' if chars.peek() == Some(&'"') || chars.peek() == Some(&'\\') => {
-                current.push(chars.next().unwrap()); // 转义
+            continue;
+        }
+        if raw.is_empty() {
+            column = i + 1;
+        }
+        raw.push(ch);
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            '\\' if matches!(chars.peek(), Some((_, '"' | '\\'))) => {
+                let (_, escaped) = chars.next().unwrap(); // 转义
+                raw.push(escaped);
+                current.push(escaped);
             }
             _ => current.push(ch),
         }
     }
 
-    if !current.is_empty() {
-        tokens.push(current);
+    if !raw.is_empty() {
+        tokens.push(Token {
+            text: current,
+            raw,
+            column,
+        });
     }
 
     if in_quotes {
@@ -55,20 +187,416 @@ pub fn parse_command(input: &str) -> Result<Command, String> {
 
     let mut tokens = tokens.into_iter();
     let name = tokens.next().unwrap(); // safe after is_empty() check
+    let tokens: Vec<Token> = tokens.collect();
+    let rest = input
+        .chars()
+        .skip(name.column + name.raw.chars().count())
+        .collect();
 
     let command = ParsedCommand {
-        name,
-        args: tokens.collect(),
+        name: name.text,
+        args: tokens.iter().map(|t| t.text.clone()).collect(),
+        tokens,
+        rest,
     };
     dispatch(command)
 }
 
+// 高亮规则配置文件：每行是一条 hl add 的参数，空行和 # 开头的行忽略
+pub fn load_highlight_rules(path: &Path) -> Result<Vec<HighlightRule>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let mut rules = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let rule = tokenize(line).and_then(|tokens| {
+            let args: Vec<String> = tokens.into_iter().map(|t| t.text).collect();
+            HighlightRule::parse(&args)
+        });
+        rules.push(rule.map_err(|e| format!("{}:{}: {e}", path.display(), i + 1))?);
+    }
+    Ok(rules)
+}
+
+// 默认的高亮规则配置文件：$XDG_CONFIG_HOME/uart_tui/highlight.conf 或 ~/.config/uart_tui/highlight.conf
+pub fn default_highlight_config() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(dir.join("uart_tui").join("highlight.conf"))
+}
+
+// 解析 hex 命令参数：未加引号的是十六进制字节（AA、0x7F、AA55），
+// 加引号的是字符串，支持 \x1b \r \n \t \0 \\ \" 转义
+fn parse_hex_parts(tokens: &[Token]) -> Result<Vec<HexPart>, String> {
+    let mut parts = Vec::new();
+    for token in tokens {
+        if token.raw.starts_with('"') {
+            parse_escaped_string(token, &mut parts)?;
+            continue;
+        }
+        let mut bytes = Vec::new();
+
+        let digits = token
+            .raw
+            .strip_prefix("0x")
+            .or_else(|| token.raw.strip_prefix("0X"))
+            .unwrap_or(&token.raw);
+        let invalid = || {
+            format!(
+                "Invalid hex byte \"{}\" at column {}",
+                token.raw, token.column
+            )
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        if digits.len() <= 2 {
+            bytes.push(u8::from_str_radix(digits, 16).map_err(|_| invalid())?);
+        } else if digits.len() % 2 == 0 && digits.len() == token.raw.len() {
+            // 连写的字节串，如 AA5501
+            for pair in digits.as_bytes().chunks(2) {
+                let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(pair, 16).map_err(|_| invalid())?);
+            }
+        } else {
+            return Err(invalid());
+        }
+        push_bytes(&mut parts, &bytes);
+    }
+    Ok(parts)
+}
+
+// 相邻的字节段合并成一段
+fn push_bytes(parts: &mut Vec<HexPart>, bytes: &[u8]) {
+    match parts.last_mut() {
+        Some(HexPart::Bytes(last)) => last.extend_from_slice(bytes),
+        _ => parts.push(HexPart::Bytes(bytes.to_vec())),
+    }
+}
+
+// 引号中的字符串：普通字符和 \r \n 等转义作为文本，\x 转义是原样发送的字节
+fn parse_escaped_string(token: &Token, parts: &mut Vec<HexPart>) -> Result<(), String> {
+    let inner = token
+        .raw
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .ok_or_else(|| format!("Malformed string at column {}", token.column))?;
+
+    let mut text = String::new();
+    let mut chars = inner.chars().enumerate().peekable();
+    while let Some((i, ch)) = chars.next() {
+        // 引号占一列，所以内容的列号要 +1
+        let column = token.column + 1 + i;
+        if ch != '\\' {
+            text.push(ch);
+            continue;
+        }
+        match chars.next().map(|(_, c)| c) {
+            Some('r') => text.push('\r'),
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some('0') => text.push('\0'),
+            Some('\\') => text.push('\\'),
+            Some('"') => text.push('"'),
+            Some('x') => {
+                let hex: String = (0..2)
+                    .filter_map(|_| chars.next_if(|(_, c)| c.is_ascii_hexdigit()))
+                    .map(|(_, c)| c)
+                    .collect();
+                if hex.len() != 2 {
+                    return Err(format!(
+                        "Invalid escape \"\\x{hex}\" at column {column}, expected two hex digits"
+                    ));
+                }
+                if !text.is_empty() {
+                    parts.push(HexPart::Text(std::mem::take(&mut text)));
+                }
+                push_bytes(parts, &[u8::from_str_radix(&hex, 16).unwrap()]);
+            }
+            Some(other) => {
+                return Err(format!("Unknown escape \"\\{other}\" at column {column}"));
+            }
+            None => return Err(format!("Dangling \"\\\" at column {column}")),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(HexPart::Text(text));
+    }
+    Ok(())
+}
+
 fn dispatch(cmd: ParsedCommand) -> Result<Command, String> {
     match cmd.name.as_str() {
         "c" => Ok(Command::ModeToUartChoice),
         "q" => Ok(Command::Quit),
+        "messages" | "msg" => Ok(Command::ShowMessages),
         "r" => Ok(Command::ModeToRateChoice),
-        "o" => Ok(Command::Open),
+        "o" | "open" => Ok(Command::Open),
+        "close" => Ok(Command::Close),
+        "reopen" => Ok(Command::Reopen),
+        "refresh" => Ok(Command::RefreshPorts),
+        "dtr" => Ok(Command::SetLines(parse_line_command(
+            ControlLine::Dtr,
+            &cmd.args,
+        )?)),
+        "rts" => Ok(Command::SetLines(parse_line_command(
+            ControlLine::Rts,
+            &cmd.args,
+        )?)),
+        "break" => Ok(Command::SendBreak(parse_break_command(&cmd.args)?)),
+        // reset <name> 执行复位序列，reset list 列出，reset def <name> <步骤...> 定义
+        "reset" => match cmd.args.first().map(String::as_str) {
+            None | Some("list") => Ok(Command::ListResets),
+            Some("def") => match cmd.args.get(1) {
+                Some(name) => Ok(Command::DefineReset(ResetSequence {
+                    name: name.clone(),
+                    steps: parse_steps(&cmd.args[2..])?,
+                })),
+                None => Err("Usage: reset def <name> dtr=on|off rts=on|off <ms> ...".to_string()),
+            },
+            Some(name) => Ok(Command::RunReset(name.to_string())),
+        },
+        "reconnect" => match cmd.args.first().map(|a| a.to_ascii_lowercase()).as_deref() {
+            Some("on") => Ok(Command::SetReconnect(true)),
+            Some("off") => Ok(Command::SetReconnect(false)),
+            _ => Err("Usage: reconnect on|off".to_string()),
+        },
+        "port" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SelectPort(arg.clone())),
+            None => Ok(Command::ModeToUartChoice),
+        },
+        "send" | "s" => {
+            if cmd.args.is_empty() {
+                return Err("Usage: send <text>".to_string());
+            }
+            // 多个空格、首尾空格和引号都原样发送
+            Ok(Command::Send(cmd.rest))
+        }
+        "hex" => {
+            if cmd.tokens.is_empty() {
+                return Err("Usage: hex <AA 55 0x7F \"text\\r\\n\" ...>".to_string());
+            }
+            Ok(Command::SendHex(parse_hex_parts(&cmd.tokens)?))
+        }
+        "raw" => Ok(Command::ModeToSendInput),
+        "term" => Ok(Command::ModeToTerminal),
+        "eol" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetLineEnding(LineEnding::parse(arg)?)),
+            None => Err("Usage: eol none|cr|lf|crlf".to_string()),
+        },
+        "newline" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetNewline(NewlineMode::parse(arg)?)),
+            None => Err("Usage: newline auto|cr|lf|crlf|show".to_string()),
+        },
+        "encoding" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetCharset(Charset::parse(arg)?)),
+            None => Err("Usage: encoding <utf-8|gbk|gb18030|latin1|...>".to_string()),
+        },
+        // 不带参数时打开对应的选择列表，带参数时直接设置
+        "baud" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetBaudRate(arg.clone())),
+            None => Ok(Command::ModeToRateChoice),
+        },
+        "databits" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetDataBits(arg.clone())),
+            None => Ok(Command::ModeToDataBitsChoice),
+        },
+        "parity" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetParity(arg.clone())),
+            None => Ok(Command::ModeToParityChoice),
+        },
+        "stopbits" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetStopBits(arg.clone())),
+            None => Ok(Command::ModeToStopBitsChoice),
+        },
+        "flow" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetFlowControl(arg.clone())),
+            None => Ok(Command::ModeToFlowControlChoice),
+        },
+        "view" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetView(ViewMode::parse(arg)?)),
+            None => Err("Usage: view text|hex|mixed".to_string()),
+        },
+        "time" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetTimestamp(Some(TimestampMode::parse(arg)?))),
+            None => Ok(Command::SetTimestamp(None)),
+        },
+        "ansi" => match cmd.args.first() {
+            Some(arg) => Ok(Command::SetAnsi(Some(AnsiMode::parse(arg)?))),
+            None => Ok(Command::SetAnsi(None)),
+        },
+        "log" => match cmd.args.first().map(String::as_str) {
+            Some("off") => Ok(Command::StopLog),
+            _ => Ok(Command::StartLog(LogOptions::parse(&cmd.args)?)),
+        },
+        "scrollback" => match cmd.args.first().map(|arg| arg.parse::<usize>()) {
+            Some(Ok(n)) if n > 0 => Ok(Command::SetScrollback(n)),
+            Some(_) => Err(format!("Invalid line count: {}", cmd.args[0])),
+            None => Err("Usage: scrollback <lines>".to_string()),
+        },
+        // 搜索本身通过 /pattern 输入，这里只切换匹配方式或清除
+        "search" => match cmd.args.first().map(String::as_str) {
+            Some("regex") => Ok(Command::SetSearchRegex(true)),
+            Some("plain") => Ok(Command::SetSearchRegex(false)),
+            Some("off") => Ok(Command::ClearSearch),
+            _ => Err("Usage: search regex|plain|off".to_string()),
+        },
+        "hl" => match cmd.args.first().map(String::as_str) {
+            None | Some("list") => Ok(Command::ListHighlights),
+            Some("add") => Ok(Command::AddHighlight(HighlightRule::parse(&cmd.args[1..])?)),
+            Some("rm") => match cmd.args.get(1).map(String::as_str) {
+                Some("all") => Ok(Command::RemoveHighlight(None)),
+                Some(n) => match n.parse::<usize>() {
+                    Ok(n) => Ok(Command::RemoveHighlight(Some(n))),
+                    Err(_) => Err(format!("Invalid rule number: {n}")),
+                },
+                None => Err("Usage: hl rm <n>|all".to_string()),
+            },
+            Some("load") => match cmd.args.get(1) {
+                Some(path) => Ok(Command::LoadHighlights(PathBuf::from(path))),
+                None => Err("Usage: hl load <path>".to_string()),
+            },
+            Some(other) => Err(format!("Unknown hl subcommand: {other} (add/list/rm/load)")),
+        },
+        "filter" => {
+            let usage = || "Usage: filter [-v] <regex> | filter off".to_string();
+            match cmd.args.first().map(String::as_str) {
+                Some("off") => Ok(Command::ClearFilter),
+                Some("-v") if cmd.args.len() > 1 => Ok(Command::SetFilter {
+                    pattern: cmd.args[1..].join(" "),
+                    invert: true,
+                }),
+                Some("-v") | None => Err(usage()),
+                Some(_) => Ok(Command::SetFilter {
+                    pattern: cmd.args.join(" "),
+                    invert: false,
+                }),
+            }
+        }
         _ => Err(format!("Unknown command: {}", cmd.name)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex_in(input: &str, charset: Charset) -> Result<Vec<u8>, String> {
+        encode_hex_parts(&parse_hex_parts(&tokenize(input)?)?, charset)
+    }
+
+    fn hex(input: &str) -> Result<Vec<u8>, String> {
+        hex_in(input, Charset::default())
+    }
+
+    #[test]
+    fn tokenize_splits_on_spaces_and_keeps_quotes() {
+        let tokens = tokenize(r#"send  "a b" c\"d"#).unwrap();
+        let texts: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, ["send", "a b", "c\"d"]);
+        assert_eq!(tokens[1].raw, "\"a b\"");
+        assert_eq!(tokens[1].column, 7);
+        assert_eq!(tokens[2].column, 13);
+    }
+
+    #[test]
+    fn tokenize_rejects_unclosed_quote() {
+        assert_eq!(tokenize("send \"abc").err().unwrap(), "Unclosed quote");
+    }
+
+    #[test]
+    fn hex_bytes() {
+        assert_eq!(hex("AA 55 01 0x7F").unwrap(), [0xAA, 0x55, 0x01, 0x7F]);
+        assert_eq!(
+            hex("aa5501 0XfF f").unwrap(),
+            [0xAA, 0x55, 0x01, 0xFF, 0x0F]
+        );
+        assert_eq!(hex("").unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn hex_bytes_report_the_bad_token() {
+        assert_eq!(
+            hex("AA GG").unwrap_err(),
+            "Invalid hex byte \"GG\" at column 4"
+        );
+        assert!(hex("AA5").unwrap_err().contains("\"AA5\" at column 1"));
+        assert!(hex("0x").is_err());
+        assert!(hex("0x123").is_err());
+    }
+
+    #[test]
+    fn hex_strings_with_escapes() {
+        assert_eq!(
+            hex(r#"01 "A\x1b\r\n\t\0\\\"" 02"#).unwrap(),
+            [0x01, b'A', 0x1B, b'\r', b'\n', b'\t', 0, b'\\', b'"', 0x02]
+        );
+        assert_eq!(hex("\"中\"").unwrap(), "中".as_bytes());
+    }
+
+    #[test]
+    fn hex_strings_use_the_charset() {
+        let gbk = Charset::parse("gbk").unwrap();
+        assert_eq!(
+            hex_in(r#"AA "中\xff\n""#, gbk).unwrap(),
+            [0xAA, 0xD6, 0xD0, 0xFF, b'\n']
+        );
+        assert_eq!(
+            parse_hex_parts(&tokenize(r#"01 02 "a\x00b" 03"#).unwrap()).unwrap(),
+            [
+                HexPart::Bytes(vec![1, 2]),
+                HexPart::Text("a".to_string()),
+                HexPart::Bytes(vec![0]),
+                HexPart::Text("b".to_string()),
+                HexPart::Bytes(vec![3]),
+            ]
+        );
+        let latin1 = Charset::parse("latin1").unwrap();
+        assert!(hex_in("\"中\"", latin1).is_err());
+    }
+
+    #[test]
+    fn hex_string_escape_errors() {
+        assert_eq!(
+            hex(r#""ab\x1""#).unwrap_err(),
+            "Invalid escape \"\\x1\" at column 4, expected two hex digits"
+        );
+        assert_eq!(
+            hex(r#"AA "\q""#).unwrap_err(),
+            "Unknown escape \"\\q\" at column 5"
+        );
+    }
+
+    #[test]
+    fn send_keeps_raw_text() {
+        let sent = |input: &str| match parse_command(input) {
+            Ok(Command::Send(text)) => text,
+            other => panic!("{input:?}: {:?}", other.err()),
+        };
+        assert_eq!(sent("send a  \"b\""), "a  \"b\"");
+        assert_eq!(sent("s  x "), " x ");
+        assert_eq!(sent("  send 中 文"), "中 文");
+        assert!(parse_command("send").is_err());
+    }
+
+    #[test]
+    fn line_ending_parse() {
+        assert_eq!(LineEnding::parse("CRLF").unwrap().as_bytes(), b"\r\n");
+        assert!(LineEnding::parse("lfcr").is_err());
+    }
+
+    #[test]
+    fn parse_command_dispatch() {
+        assert!(matches!(parse_command("q"), Ok(Command::Quit)));
+        assert!(matches!(
+            parse_command("hex AA \"\\n\""),
+            Ok(Command::SendHex(parts)) if parts == [HexPart::Bytes(vec![0xAA]), HexPart::Text("\n".to_string())]
+        ));
+        assert_eq!(parse_command("   ").err().unwrap(), "Empty command");
+        assert!(parse_command("no-such-command").is_err());
+    }
+}
@@ -9,22 +9,66 @@ use ratatui::{Frame, layout::Rect};
 
 pub struct CommandInputComponent {
     state: TextInputState,
+    // 发送模式：每次回车直接把这一行写入串口，而不是解析为命令
+    send_mode: bool,
+    // 实时搜索的表达式无效时的错误，显示在标题里，回车时才弹窗报告
+    search_error: Option<String>,
 }
 
 impl CommandInputComponent {
     pub fn new() -> Self {
         Self {
             state: TextInputState::default(),
+            send_mode: false,
+            search_error: None,
         }
     }
+
+    pub fn set_send_mode(&mut self, send_mode: bool) {
+        self.send_mode = send_mode;
+    }
+
+    pub fn set_search_error(&mut self, error: Option<String>) {
+        self.search_error = error;
+    }
 }
 
 impl Component for CommandInputComponent {
     fn handle_key_events(&mut self, key: KeyEvent) -> Result<Action> {
-        // 先让 state 处理输入
-        let input_result = self.state.handle_key(key);
+        // Tab 把焦点切到接收区，用于滚动查看历史数据
+        if key.code == KeyCode::Tab {
+            return Ok(Action::SwitchMode(crate::Mode::Receive));
+        }
+
+        // 先让 state 处理输入，按下 Enter 时才会返回内容
+        let searching = self.state.value().starts_with('/');
+        let Some(input_result) = self.state.handle_key(key) else {
+            // 以 '/' 开头的输入是搜索，每次按键都实时更新结果
+            if !self.send_mode {
+                match self.state.value().strip_prefix('/') {
+                    Some(query) => return Ok(Action::Search(query.to_string())),
+                    None if searching => return Ok(Action::Search(String::new())),
+                    None => {}
+                }
+            }
+            return Ok(Action::None);
+        };
+
+        if self.send_mode {
+            return Ok(Action::Send(input_result));
+        }
+
+        // 搜索已经实时执行过，回车后把焦点交给接收区，用 n / N 跳转
+        if let Some(query) = input_result.strip_prefix('/') {
+            if let Some(e) = self.search_error.take() {
+                return Ok(Action::Error(e));
+            }
+            if query.is_empty() {
+                return Ok(Action::None);
+            }
+            return Ok(Action::SwitchMode(crate::Mode::Receive));
+        }
 
-        // 如果按下了 Enter，TextInputState 会返回非空字符串
         if !input_result.is_empty() {
             match parse_command(&input_result) {
                 Ok(Command::ModeToUartChoice) => {
@@ -33,8 +77,63 @@ impl Component for CommandInputComponent {
                 Ok(Command::ModeToRateChoice) => {
                     return Ok(Action::SwitchMode(crate::Mode::RateChoice));
                 }
+                Ok(Command::ModeToSendInput) => {
+                    return Ok(Action::SwitchMode(crate::Mode::SendInput));
+                }
+                Ok(Command::ModeToTerminal) => {
+                    return Ok(Action::SwitchMode(crate::Mode::Terminal));
+                }
+                Ok(Command::ModeToDataBitsChoice) => {
+                    return Ok(Action::SwitchMode(crate::Mode::DataBitsChoice));
+                }
+                Ok(Command::ModeToParityChoice) => {
+                    return Ok(Action::SwitchMode(crate::Mode::ParityChoice));
+                }
+                Ok(Command::ModeToStopBitsChoice) => {
+                    return Ok(Action::SwitchMode(crate::Mode::StopBitsChoice));
+                }
+                Ok(Command::ModeToFlowControlChoice) => {
+                    return Ok(Action::SwitchMode(crate::Mode::FlowControlChoice));
+                }
+                Ok(Command::SetBaudRate(v)) => return Ok(Action::SelectRate(v)),
+                Ok(Command::SetDataBits(v)) => return Ok(Action::SelectDataBits(v)),
+                Ok(Command::SetParity(v)) => return Ok(Action::SelectParity(v)),
+                Ok(Command::SetStopBits(v)) => return Ok(Action::SelectStopBits(v)),
+                Ok(Command::SetFlowControl(v)) => return Ok(Action::SelectFlowControl(v)),
                 Ok(Command::Quit) => return Ok(Action::Quit),
+                Ok(Command::ShowMessages) => return Ok(Action::ShowMessages),
                 Ok(Command::Open) => return Ok(Action::Open),
+                Ok(Command::Close) => return Ok(Action::Close),
+                Ok(Command::Reopen) => return Ok(Action::Reopen),
+                Ok(Command::RefreshPorts) => return Ok(Action::RefreshPorts),
+                Ok(Command::SetLines(steps)) => return Ok(Action::SetLines(steps)),
+                Ok(Command::SendBreak(duration)) => return Ok(Action::SendBreak(duration)),
+                Ok(Command::RunReset(name)) => return Ok(Action::RunReset(name)),
+                Ok(Command::DefineReset(seq)) => return Ok(Action::DefineReset(seq)),
+                Ok(Command::ListResets) => return Ok(Action::ListResets),
+                Ok(Command::SetReconnect(enabled)) => return Ok(Action::SetReconnect(enabled)),
+                Ok(Command::SelectPort(spec)) => return Ok(Action::SelectPort(spec)),
+                Ok(Command::Send(text)) => return Ok(Action::Send(text)),
+                Ok(Command::SendHex(parts)) => return Ok(Action::SendHex(parts)),
+                Ok(Command::SetLineEnding(ending)) => return Ok(Action::SetLineEnding(ending)),
+                Ok(Command::SetCharset(charset)) => return Ok(Action::SetCharset(charset)),
+                Ok(Command::SetNewline(mode)) => return Ok(Action::SetNewline(mode)),
+                Ok(Command::SetView(view)) => return Ok(Action::SetView(view)),
+                Ok(Command::SetTimestamp(mode)) => return Ok(Action::SetTimestamp(mode)),
+                Ok(Command::SetAnsi(mode)) => return Ok(Action::SetAnsi(mode)),
+                Ok(Command::StartLog(options)) => return Ok(Action::StartLog(options)),
+                Ok(Command::StopLog) => return Ok(Action::StopLog),
+                Ok(Command::SetScrollback(n)) => return Ok(Action::SetScrollback(n)),
+                Ok(Command::SetSearchRegex(regex)) => return Ok(Action::SetSearchRegex(regex)),
+                Ok(Command::ClearSearch) => return Ok(Action::Search(String::new())),
+                Ok(Command::AddHighlight(rule)) => return Ok(Action::AddHighlight(rule)),
+                Ok(Command::ListHighlights) => return Ok(Action::ListHighlights),
+                Ok(Command::RemoveHighlight(n)) => return Ok(Action::RemoveHighlight(n)),
+                Ok(Command::LoadHighlights(path)) => return Ok(Action::LoadHighlights(path)),
+                Ok(Command::SetFilter { pattern, invert }) => {
+                    return Ok(Action::SetFilter(pattern, invert));
+                }
+                Ok(Command::ClearFilter) => return Ok(Action::ClearFilter),
                 Err(e) => return Ok(Action::Error(e)),
             }
         }
@@ -45,6 +144,13 @@ impl Component for CommandInputComponent {
 
     fn render(&mut self, f: &mut Frame, area: Rect, is_active: bool) {
         self.state.set_focus(is_active);
-        f.render_stateful_widget(TextInput, area, &mut self.state);
+        let title = if self.send_mode {
+            "Send (Esc 退出)".to_string()
+        } else if let Some(e) = &self.search_error {
+            format!("Input [无效的搜索: {e}]")
+        } else {
+            "Input".to_string()
+        };
+        f.render_stateful_widget(TextInput::new(title), area, &mut self.state);
     }
 }
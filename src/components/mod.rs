@@ -1,6 +1,6 @@
 use crate::action::Action;
 use color_eyre::Result;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::{Frame, layout::Rect};
 mod list_component;
 pub use list_component::*;
@@ -8,10 +8,19 @@ mod input_component;
 pub use input_component::*;
 mod receive_component;
 pub use receive_component::*;
+mod status_component;
+pub use status_component::*;
+mod notification_component;
+pub use notification_component::*;
 pub trait Component {
     // 处理按键，返回一个 Action 告诉 App 该做什么
     fn handle_key_events(&mut self, key: KeyEvent) -> Result<Action>;
 
+    // 处理鼠标事件，默认忽略
+    fn handle_mouse_events(&mut self, _mouse: MouseEvent) -> Result<Action> {
+        Ok(Action::None)
+    }
+
     // 渲染自己
     // is_active 用于判断是否需要高亮边框
     fn render(&mut self, f: &mut Frame, area: Rect, is_active: bool);
@@ -0,0 +1,305 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use super::*;
+use chrono::{DateTime, Local};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Wrap},
+};
+use unicode_width::UnicodeWidthStr;
+
+// 提示消息停留的时间
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+// 同时显示的提示消息数
+const MAX_TOASTS: usize = 3;
+// 历史最多保留的消息数，以及最多排队等待关闭的错误数，超出时丢弃最旧的
+const MAX_HISTORY: usize = 1000;
+const MAX_ERRORS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn label(&self) -> &'static str {
+        match self {
+            Level::Info => "信息",
+            Level::Warning => "警告",
+            Level::Error => "错误",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Level::Info => Color::LightCyan,
+            Level::Warning => Color::Yellow,
+            Level::Error => Color::LightRed,
+        }
+    }
+}
+
+struct Message {
+    time: DateTime<Local>,
+    level: Level,
+    text: String,
+}
+
+// 通知：信息 / 警告以提示消息的形式显示几秒后消失，错误弹出对话框，必须按键关闭；
+// 所有消息都记入历史，messages 命令可以翻看
+pub struct NotificationComponent {
+    history: VecDeque<Message>,
+    dropped: usize, // 已从历史中丢弃的消息数，下面的编号都从最早一条消息算起
+    toasts: VecDeque<(usize, Instant)>, // 消息编号 + 消失时间
+    errors: VecDeque<usize>, // 等待关闭的错误，依次弹出
+    // 消息历史窗口：None 表示未打开，Some(n) 表示从最新一条往上滚动了 n 行
+    history_scroll: Option<usize>,
+    history_height: usize, // 上次渲染时历史窗口能显示的行数，用于翻页
+    history_max: usize,    // 上次渲染时最多能往上滚动的行数（长消息会换行）
+}
+
+impl NotificationComponent {
+    pub fn new() -> Self {
+        Self {
+            history: VecDeque::new(),
+            dropped: 0,
+            toasts: VecDeque::new(),
+            errors: VecDeque::new(),
+            history_scroll: None,
+            history_height: 0,
+            history_max: 0,
+        }
+    }
+
+    pub fn push(&mut self, level: Level, text: String) {
+        let index = self.dropped + self.history.len();
+        self.history.push_back(Message {
+            time: Local::now(),
+            level,
+            text,
+        });
+        match level {
+            Level::Error => {
+                self.errors.push_back(index);
+                while self.errors.len() > MAX_ERRORS {
+                    self.errors.pop_front();
+                }
+            }
+            _ => {
+                self.toasts
+                    .push_back((index, Instant::now() + TOAST_DURATION));
+                while self.toasts.len() > MAX_TOASTS {
+                    self.toasts.pop_front();
+                }
+            }
+        }
+        while self.history.len() > MAX_HISTORY {
+            self.history.pop_front();
+            self.dropped += 1;
+        }
+        let dropped = self.dropped;
+        self.errors.retain(|&i| i >= dropped);
+        self.toasts.retain(|&(i, _)| i >= dropped);
+    }
+
+    fn message(&self, index: usize) -> &Message {
+        &self.history[index - self.dropped]
+    }
+
+    pub fn open_history(&mut self) {
+        self.history_scroll = Some(0);
+    }
+
+    // 有弹窗时按键只交给通知组件处理
+    pub fn is_modal(&self) -> bool {
+        !self.errors.is_empty() || self.history_scroll.is_some()
+    }
+
+    fn render_toasts(&mut self, f: &mut Frame, area: Rect) {
+        let now = Instant::now();
+        self.toasts.retain(|(_, expires)| *expires > now);
+        let mut y = area.y + 1;
+        for (index, _) in &self.toasts {
+            let message = self.message(*index);
+            let width = u16::try_from(message.text.width())
+                .unwrap_or(u16::MAX)
+                .saturating_add(4)
+                .min(area.width.saturating_sub(2));
+            if y + 3 > area.bottom() {
+                break;
+            }
+            let rect = Rect::new(area.right().saturating_sub(width + 1), y, width, 3);
+            let color = message.level.color();
+            f.render_widget(Clear, rect);
+            f.render_widget(
+                Paragraph::new(message.text.as_str()).block(
+                    Block::bordered()
+                        .border_style(Style::new().fg(color))
+                        .title(Span::styled(message.level.label(), Style::new().fg(color))),
+                ),
+                rect,
+            );
+            y += 3;
+        }
+    }
+
+    fn render_history(&mut self, f: &mut Frame, area: Rect, scroll: usize) {
+        let rect = centered(area, area.width * 4 / 5, area.height * 4 / 5);
+        let lines: Vec<Line> = self
+            .history
+            .iter()
+            .map(|m| {
+                Line::from(vec![
+                    Span::styled(
+                        m.time.format("%H:%M:%S ").to_string(),
+                        Style::new().fg(Color::DarkGray),
+                    ),
+                    Span::styled(
+                        format!("[{}] ", m.level.label()),
+                        Style::new().fg(m.level.color()),
+                    ),
+                    Span::raw(m.text.as_str()),
+                ])
+            })
+            .collect();
+        let title = format!(
+            "消息记录 {} 条 (↑↓ PgUp PgDn 滚动, Esc 关闭)",
+            self.history.len()
+        );
+        let popup = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::bordered()
+                .border_style(Style::new().fg(Color::LightYellow))
+                .title(title),
+        );
+        // 换行后的总行数（去掉边框），默认显示最新的消息
+        let rows = popup.line_count(rect.width).saturating_sub(2);
+        self.history_height = rect.height.saturating_sub(2) as usize;
+        self.history_max = rows.saturating_sub(self.history_height);
+        let scroll = scroll.min(self.history_max);
+        self.history_scroll = Some(scroll);
+        let top = (self.history_max - scroll) as u16;
+        f.render_widget(Clear, rect);
+        f.render_widget(popup.scroll((top, 0)), rect);
+    }
+
+    fn render_error(&self, f: &mut Frame, area: Rect, index: usize) {
+        let message = self.message(index);
+        let title = if self.errors.len() > 1 {
+            format!("错误 (还有 {} 条)", self.errors.len() - 1)
+        } else {
+            "错误".to_string()
+        };
+        let text = vec![
+            Line::raw(message.text.as_str()),
+            Line::raw(""),
+            Line::styled("Enter / Esc 关闭", Style::new().fg(Color::DarkGray)),
+        ];
+        let popup = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+            Block::bordered()
+                .border_style(Style::new().fg(Color::LightRed))
+                .title(Span::styled(
+                    title,
+                    Style::new()
+                        .fg(Color::LightRed)
+                        .add_modifier(Modifier::BOLD),
+                )),
+        );
+        // 高度按换行后的实际行数（含边框）
+        let width = 60.min(area.width.saturating_sub(4));
+        let height = popup.line_count(width) as u16;
+        let rect = centered(area, width, height);
+        f.render_widget(Clear, rect);
+        f.render_widget(popup, rect);
+    }
+}
+
+// area 中居中的 width x height 区域
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+impl Component for NotificationComponent {
+    fn handle_key_events(&mut self, key: KeyEvent) -> Result<Action> {
+        // 错误弹窗优先，逐条关闭
+        if !self.errors.is_empty() {
+            if matches!(key.code, KeyCode::Enter | KeyCode::Esc | KeyCode::Char(' ')) {
+                self.errors.pop_front();
+            }
+            return Ok(Action::None);
+        }
+        let Some(scroll) = self.history_scroll else {
+            return Ok(Action::None);
+        };
+        let page = self.history_height.max(1);
+        let scroll = match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                self.history_scroll = None;
+                return Ok(Action::None);
+            }
+            KeyCode::Up | KeyCode::Char('k') => scroll + 1,
+            KeyCode::Down | KeyCode::Char('j') => scroll.saturating_sub(1),
+            KeyCode::PageUp => scroll + page,
+            KeyCode::PageDown => scroll.saturating_sub(page),
+            KeyCode::Home | KeyCode::Char('g') => self.history_max,
+            KeyCode::End | KeyCode::Char('G') => 0,
+            _ => scroll,
+        };
+        self.history_scroll = Some(scroll.min(self.history_max));
+        Ok(Action::None)
+    }
+
+    fn render(&mut self, f: &mut Frame, area: Rect, _is_active: bool) {
+        self.render_toasts(f, area);
+        if let Some(scroll) = self.history_scroll {
+            self.render_history(f, area, scroll);
+        }
+        if let Some(&index) = self.errors.front() {
+            self.render_error(f, area, index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_and_error_queue_are_bounded() {
+        let mut n = NotificationComponent::new();
+        for i in 0..MAX_HISTORY + 5 {
+            let level = if i % 2 == 0 {
+                Level::Error
+            } else {
+                Level::Info
+            };
+            n.push(level, format!("message {i}"));
+        }
+        assert_eq!(n.history.len(), MAX_HISTORY);
+        assert_eq!(n.history[0].text, "message 5");
+        assert_eq!(n.errors.len(), MAX_ERRORS);
+        assert_eq!(n.toasts.len(), MAX_TOASTS);
+        // 排队的错误都还在历史中，最早的一条在最前面
+        let first = *n.errors.front().unwrap();
+        assert_eq!(
+            n.message(first).text,
+            format!("message {}", MAX_HISTORY + 4 - 2 * (MAX_ERRORS - 1))
+        );
+        assert!(n.is_modal());
+    }
+}
@@ -1,29 +1,137 @@
-use crate::{
-    command::*,
-    widgets::{TextInput, TextInputState},
-};
-
 use super::*;
-use crate::widgets::*;
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::{encoding::Charset, widgets::*};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 use ratatui::{Frame, layout::Rect};
 
+// 鼠标滚轮每格滚动的行数
+const WHEEL_LINES: usize = 3;
+
 pub struct ReceiveComponent {
     pub state: ReceiveTextState,
+    // 终端模式下刚按过 Ctrl-A，下一个键是命令而不是发给串口
+    escape_pending: bool,
 }
 impl ReceiveComponent {
     pub fn new() -> Self {
         Self {
             state: ReceiveTextState::default(),
+            escape_pending: false,
+        }
+    }
+
+    // 终端屏幕对查询（如 ESP-IDF 控制台的 ESC[5n）的应答，原样写回串口
+    pub fn terminal_replies(&mut self) -> Action {
+        let replies = self.state.take_terminal_replies();
+        if replies.is_empty() {
+            Action::None
+        } else {
+            Action::SendRaw(replies)
+        }
+    }
+
+    // 终端模式：按键转换为字节直接发给串口，Ctrl-A x 退出，Ctrl-A a 发送 Ctrl-A 本身
+    fn handle_terminal_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl_a =
+            key.code == KeyCode::Char('a') && key.modifiers.contains(KeyModifiers::CONTROL);
+        if std::mem::take(&mut self.escape_pending) {
+            return match key.code {
+                KeyCode::Char('x' | 'X') => Action::SwitchMode(crate::Mode::CommandInput),
+                KeyCode::Char('a') => Action::SendRaw(vec![0x01]),
+                _ => Action::None,
+            };
+        }
+        if ctrl_a {
+            self.escape_pending = true;
+            return Action::None;
+        }
+        match key_bytes(key, self.state.charset()) {
+            Some(Ok(bytes)) => Action::SendRaw(bytes),
+            Some(Err(e)) => Action::Error(e),
+            None => Action::None,
+        }
+    }
+}
+
+// 按键对应的终端输入字节，功能键使用 VT100 / xterm 序列，字符按当前字符集编码
+fn key_bytes(key: KeyEvent, charset: Charset) -> Option<Result<Vec<u8>, String>> {
+    let mut bytes = match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            match c.to_ascii_lowercase() {
+                c @ 'a'..='z' => vec![c as u8 - b'a' + 1],
+                '@' | ' ' | '2' => vec![0x00],
+                '[' | '3' => vec![0x1B],
+                '\\' | '4' => vec![0x1C],
+                ']' | '5' => vec![0x1D],
+                '^' | '6' => vec![0x1E],
+                '_' | '7' | '/' => vec![0x1F],
+                '?' | '8' => vec![0x7F],
+                _ => return None,
+            }
         }
+        KeyCode::Char(c) => match charset.encode(&c.to_string()) {
+            Ok(bytes) => bytes,
+            Err(e) => return Some(Err(e)),
+        },
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Tab => vec![b'\t'],
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Backspace => vec![0x7F],
+        KeyCode::Esc => vec![0x1B],
+        KeyCode::Up => b"\x1b[A".to_vec(),
+        KeyCode::Down => b"\x1b[B".to_vec(),
+        KeyCode::Right => b"\x1b[C".to_vec(),
+        KeyCode::Left => b"\x1b[D".to_vec(),
+        KeyCode::Home => b"\x1b[H".to_vec(),
+        KeyCode::End => b"\x1b[F".to_vec(),
+        KeyCode::Insert => b"\x1b[2~".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        KeyCode::F(n @ 1..=4) => vec![0x1B, b'O', b'P' + n - 1],
+        KeyCode::F(n @ 5..=12) => {
+            let code = [15, 17, 18, 19, 20, 21, 23, 24][n as usize - 5];
+            format!("\x1b[{code}~").into_bytes()
+        }
+        _ => return None,
+    };
+    // Alt 组合键按惯例加 ESC 前缀
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        bytes.insert(0, 0x1B);
     }
+    Some(Ok(bytes))
 }
 
 impl Component for ReceiveComponent {
     fn handle_key_events(&mut self, key: KeyEvent) -> Result<Action> {
+        if self.state.is_terminal() {
+            return Ok(self.handle_terminal_key(key));
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.state.scroll_up(1),
+            KeyCode::Down | KeyCode::Char('j') => self.state.scroll_down(1),
+            KeyCode::PageUp => self.state.page_up(),
+            KeyCode::PageDown => self.state.page_down(),
+            KeyCode::Home | KeyCode::Char('g') => self.state.scroll_to_top(),
+            KeyCode::End | KeyCode::Char('G') => self.state.scroll_to_bottom(),
+            KeyCode::Char('n') => self.state.search_next(),
+            KeyCode::Char('N') => self.state.search_prev(),
+            KeyCode::Tab => return Ok(Action::SwitchMode(crate::Mode::CommandInput)),
+            _ => {}
+        }
         Ok(Action::None)
     }
+
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> Result<Action> {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.state.scroll_up(WHEEL_LINES),
+            MouseEventKind::ScrollDown => self.state.scroll_down(WHEEL_LINES),
+            _ => {}
+        }
+        Ok(Action::None)
+    }
+
     fn render(&mut self, f: &mut Frame, area: Rect, is_active: bool) {
+        self.state.set_focus(is_active);
         f.render_stateful_widget(ReceiveText, area, &mut self.state);
     }
 }
@@ -0,0 +1,156 @@
+use std::time::Instant;
+
+use super::*;
+use crate::{Mode, logger::format_size};
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+// 串口连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Closed,
+    Open,
+    Reconnecting, // 设备断开，正在等待它重新出现
+}
+
+// 状态栏显示的内容，每帧由 App 填写
+pub struct StatusInfo {
+    pub port: String,     // 串口名，没有选中串口时为空
+    pub settings: String, // 如 "115200 8N1 none"
+    pub state: ConnectionState,
+    pub mode: Mode,
+    pub log: Option<String>,    // 正在写入的日志文件
+    pub filter: Option<String>, // 当前的行过滤条件
+}
+
+// 底部状态栏：连接状态、参数、收发计数和速率、当前模式、日志和过滤
+pub struct StatusBarComponent {
+    info: Option<StatusInfo>,
+    rx_total: u64,
+    tx_total: u64,
+    // 速率按约 1 秒的窗口统计
+    window_start: Instant,
+    window_rx: u64,
+    window_tx: u64,
+    rx_rate: u64,
+    tx_rate: u64,
+}
+
+impl StatusBarComponent {
+    pub fn new() -> Self {
+        Self {
+            info: None,
+            rx_total: 0,
+            tx_total: 0,
+            window_start: Instant::now(),
+            window_rx: 0,
+            window_tx: 0,
+            rx_rate: 0,
+            tx_rate: 0,
+        }
+    }
+
+    pub fn set_info(&mut self, info: StatusInfo) {
+        self.info = Some(info);
+    }
+
+    pub fn add_rx(&mut self, n: usize) {
+        self.rx_total += n as u64;
+        self.window_rx += n as u64;
+    }
+
+    pub fn add_tx(&mut self, n: usize) {
+        self.tx_total += n as u64;
+        self.window_tx += n as u64;
+    }
+
+    // 打开新的串口时计数清零
+    pub fn reset_counters(&mut self) {
+        *self = Self {
+            info: self.info.take(),
+            ..Self::new()
+        };
+    }
+
+    // 窗口满 1 秒后计算速率并开始新的窗口
+    fn update_rates(&mut self) {
+        let elapsed = self.window_start.elapsed().as_secs_f64();
+        if elapsed < 1.0 {
+            return;
+        }
+        self.rx_rate = (self.window_rx as f64 / elapsed) as u64;
+        self.tx_rate = (self.window_tx as f64 / elapsed) as u64;
+        self.window_start = Instant::now();
+        self.window_rx = 0;
+        self.window_tx = 0;
+    }
+}
+
+impl Component for StatusBarComponent {
+    // 状态栏不接收焦点
+    fn handle_key_events(&mut self, _key: KeyEvent) -> Result<Action> {
+        Ok(Action::None)
+    }
+
+    fn render(&mut self, f: &mut Frame, area: Rect, _is_active: bool) {
+        self.update_rates();
+        let Some(info) = &self.info else {
+            return;
+        };
+        let separator = || Span::styled(" │ ", Style::new().fg(Color::DarkGray));
+        let (state, color) = match info.state {
+            ConnectionState::Open => ("● 已连接", Color::LightGreen),
+            ConnectionState::Closed => ("○ 未连接", Color::Gray),
+            ConnectionState::Reconnecting => ("⟳ 重连中", Color::Yellow),
+        };
+        let port = if info.port.is_empty() {
+            "未选择串口"
+        } else {
+            info.port.as_str()
+        };
+        let mut spans = vec![
+            Span::styled(
+                format!(" {state} "),
+                Style::new().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(port.to_string()),
+            separator(),
+            Span::raw(info.settings.clone()),
+            separator(),
+            Span::raw(format!(
+                "RX {} {}/s",
+                format_size(self.rx_total),
+                format_size(self.rx_rate)
+            )),
+            separator(),
+            Span::raw(format!(
+                "TX {} {}/s",
+                format_size(self.tx_total),
+                format_size(self.tx_rate)
+            )),
+            separator(),
+            Span::styled(info.mode.label(), Style::new().fg(Color::LightYellow)),
+        ];
+        if let Some(log) = &info.log {
+            spans.push(separator());
+            spans.push(Span::styled(
+                format!("● LOG {log}"),
+                Style::new().fg(Color::LightRed),
+            ));
+        }
+        if let Some(filter) = &info.filter {
+            spans.push(separator());
+            spans.push(Span::styled(
+                format!("过滤 {filter}"),
+                Style::new().fg(Color::LightMagenta),
+            ));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+}
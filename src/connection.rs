@@ -0,0 +1,270 @@
+use std::{
+    collections::VecDeque,
+    io::{ErrorKind, Read},
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use serialport::SerialPort;
+
+use crate::{
+    event::{AppEvent, SerialEvent},
+    modem::{ControlLine, LineStep, ModemLines},
+};
+
+// 后台线程单次读取的缓冲区大小，高波特率下一次可以取走驱动里积压的全部数据
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+// 读超时，同时也是处理请求和控制序列等待的最大延迟
+pub const READ_TIMEOUT: Duration = Duration::from_millis(10);
+// 一次写请求最多等待多久（硬件流控被对端拉停时会一直超时）
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+// 读取 CTS / DSR / CD / RI 的间隔
+const MODEM_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+enum Request {
+    Write { data: Vec<u8>, echo: Option<String> },
+    Control(Vec<LineStep>),
+    Stop,
+}
+
+// 一个已打开的串口连接：读写由后台线程负责，
+// port 是同一设备的另一个句柄，供 UI 线程修改波特率等参数
+pub struct SerialConnection {
+    pub port: Box<dyn SerialPort>,
+    id: u64,
+    requests: Sender<Request>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SerialConnection {
+    pub fn start(
+        port: Box<dyn SerialPort>,
+        id: u64,
+        events: Sender<AppEvent>,
+    ) -> serialport::Result<Self> {
+        let io_port = port.try_clone()?;
+        let (requests, rx) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name(format!("serial-{id}"))
+            .spawn(move || io_loop(io_port, id, rx, events))?;
+        Ok(Self {
+            port,
+            id,
+            requests,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    // 写入在后台线程完成，结果通过 SerialEvent::Sent / WriteFailed 返回
+    // echo 为 None 时不在接收区回显（终端模式下由对端回显）
+    pub fn write(&self, data: Vec<u8>, echo: Option<String>) {
+        let _ = self.requests.send(Request::Write { data, echo });
+    }
+
+    // 按顺序设置 DTR / RTS / BREAK，含等待的序列也在后台线程执行，不会卡住 UI；
+    // 等待期间照常接收数据，之后的写请求排在序列后面
+    pub fn control(&self, steps: Vec<LineStep>) {
+        let _ = self.requests.send(Request::Control(steps));
+    }
+}
+
+impl Drop for SerialConnection {
+    fn drop(&mut self) {
+        let _ = self.requests.send(Request::Stop);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// 与 Write::write_all 相同，但把读写共用的短超时当作“稍后重试”，
+// 连续 WRITE_TIMEOUT 没有写出任何数据才算失败；每次写之间 stop 返回 true 时放弃，返回 Ok(false)
+fn write_all(
+    port: &mut dyn SerialPort,
+    mut data: &[u8],
+    stop: &mut dyn FnMut() -> bool,
+) -> std::io::Result<bool> {
+    let mut last_progress = Instant::now();
+    while !data.is_empty() {
+        match port.write(data) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(n) => {
+                data = &data[n..];
+                last_progress = Instant::now();
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::Interrupted) => {
+                if last_progress.elapsed() > WRITE_TIMEOUT {
+                    return Err(e);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+        if !data.is_empty() && stop() {
+            return Ok(false);
+        }
+    }
+    port.flush().map(|()| true)
+}
+
+// 收下积压的请求放进 queued，收到 Stop 或 UI 端已经关闭时返回 true
+fn stop_requested(requests: &Receiver<Request>, queued: &mut VecDeque<Request>) -> bool {
+    loop {
+        match requests.try_recv() {
+            Ok(Request::Stop) | Err(TryRecvError::Disconnected) => return true,
+            Ok(request) => queued.push_back(request),
+            Err(TryRecvError::Empty) => return false,
+        }
+    }
+}
+
+// 执行一步控制操作，Wait 不在这里睡眠，而是返回要等待的时间，由 io_loop 边读数据边等
+fn run_step(
+    port: &mut dyn SerialPort,
+    step: LineStep,
+    lines: &mut ModemLines,
+) -> serialport::Result<Option<Duration>> {
+    match step {
+        LineStep::Set(ControlLine::Dtr, level) => {
+            port.write_data_terminal_ready(level)?;
+            lines.dtr = Some(level);
+        }
+        LineStep::Set(ControlLine::Rts, level) => {
+            port.write_request_to_send(level)?;
+            lines.rts = Some(level);
+        }
+        LineStep::Break(true) => port.set_break()?,
+        LineStep::Break(false) => port.clear_break()?,
+        LineStep::Wait(duration) => return Ok(Some(duration)),
+    }
+    Ok(None)
+}
+
+// 刚开始的 BREAK 会持续多久：累加到 break=off 之前的等待，序列里没有 break=off 时返回 None
+fn break_duration(steps: &VecDeque<LineStep>) -> Option<Duration> {
+    let mut total = Duration::ZERO;
+    for step in steps {
+        match step {
+            LineStep::Wait(d) => total += *d,
+            LineStep::Break(false) => return Some(total),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn io_loop(
+    mut port: Box<dyn SerialPort>,
+    id: u64,
+    requests: Receiver<Request>,
+    events: Sender<AppEvent>,
+) {
+    let send = |event| events.send(AppEvent::Serial(id, event)).is_ok();
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    if port.set_timeout(READ_TIMEOUT).is_err() {
+        send(SerialEvent::Disconnected(
+            "failed to set timeout".to_string(),
+        ));
+        return;
+    }
+
+    // 控制线状态有变化时才通知 UI，None 保证打开后先发一次
+    let mut lines = ModemLines::default();
+    let mut sent_lines = None;
+    let mut next_poll = Instant::now();
+    // 正在执行的控制序列的剩余步骤，以及当前等待结束的时刻
+    let mut steps: VecDeque<LineStep> = VecDeque::new();
+    let mut resume_at: Option<Instant> = None;
+    // 序列执行期间收到的请求，序列结束后按顺序处理
+    let mut queued: VecDeque<Request> = VecDeque::new();
+
+    loop {
+        // 先收下积压的请求，Stop 随时生效，不用等正在执行的序列
+        if stop_requested(&requests, &mut queued) {
+            return;
+        }
+
+        // 执行控制序列直到遇到还没结束的等待，序列执行完再处理排队的请求
+        loop {
+            if let Some(at) = resume_at {
+                if Instant::now() < at {
+                    break;
+                }
+                resume_at = None;
+            }
+            if let Some(step) = steps.pop_front() {
+                match run_step(port.as_mut(), step, &mut lines) {
+                    Ok(wait) => {
+                        resume_at = wait.map(|d| Instant::now() + d);
+                        if step == LineStep::Break(true)
+                            && !send(SerialEvent::Break(break_duration(&steps)))
+                        {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        steps.clear();
+                        if !send(SerialEvent::ControlFailed(e.to_string())) {
+                            return;
+                        }
+                    }
+                }
+                continue;
+            }
+            // 每个排队的写请求之前都看一眼 Stop，关闭时直接丢弃剩下的请求
+            if stop_requested(&requests, &mut queued) {
+                return;
+            }
+            match queued.pop_front() {
+                Some(Request::Write { data, echo }) => {
+                    let mut stop = || stop_requested(&requests, &mut queued);
+                    let event = match write_all(port.as_mut(), &data, &mut stop) {
+                        Ok(false) => return,
+                        Ok(true) => SerialEvent::Sent {
+                            data,
+                            echo,
+                            time: Local::now(),
+                        },
+                        Err(e) => SerialEvent::WriteFailed(e.to_string()),
+                    };
+                    if !send(event) {
+                        return;
+                    }
+                }
+                Some(Request::Control(sequence)) => steps = sequence.into(),
+                Some(Request::Stop) => return,
+                None => break,
+            }
+        }
+
+        if Instant::now() >= next_poll {
+            next_poll = Instant::now() + MODEM_POLL_INTERVAL;
+            lines.poll(port.as_mut());
+        }
+        if sent_lines != Some(lines) {
+            sent_lines = Some(lines);
+            if !send(SerialEvent::Modem(lines)) {
+                return;
+            }
+        }
+
+        match port.read(&mut buffer) {
+            Ok(0) => {}
+            Ok(n) => {
+                if !send(SerialEvent::Data(buffer[..n].to_vec(), Local::now())) {
+                    return;
+                }
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::Interrupted) => {}
+            Err(e) => {
+                send(SerialEvent::Disconnected(e.to_string()));
+                return;
+            }
+        }
+    }
+}
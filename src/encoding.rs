@@ -0,0 +1,107 @@
+use std::borrow::Cow;
+
+use encoding_rs::{Decoder, Encoding, UTF_8};
+
+// 收发使用的字符集，名称按 WHATWG 标签解析：utf-8 / gbk / gb18030 / latin1 / big5 / shift_jis ...
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Charset(&'static Encoding);
+
+impl Default for Charset {
+    fn default() -> Self {
+        Charset(UTF_8)
+    }
+}
+
+impl Charset {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let encoding = Encoding::for_label(s.trim().as_bytes())
+            .ok_or_else(|| format!("Unknown encoding: {s} (utf-8/gbk/gb18030/latin1/big5/...)"))?;
+        // UTF-16 编码时会变成 UTF-8，replacement 把任何输入都解码成一个 U+FFFD，
+        // 收发不对称的字符集都不支持
+        if encoding.output_encoding() != encoding {
+            return Err(format!(
+                "Unsupported encoding: {s} ({} cannot be used for both sending and receiving)",
+                encoding.name()
+            ));
+        }
+        Ok(Charset(encoding))
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.0 == UTF_8
+    }
+
+    // 解码一段完整的数据，非法字节替换为 U+FFFD
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Cow<'a, str> {
+        self.0.decode_without_bom_handling(bytes).0
+    }
+
+    // 流式解码器，多字节字符被拆在两次读取之间时会等待后续字节
+    pub fn decoder(&self) -> Decoder {
+        self.0.new_decoder_without_bom_handling()
+    }
+
+    // 把要发送的文本编码为字节，有字符无法用该字符集表示时报错
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, String> {
+        let (bytes, _, had_errors) = self.0.encode(text);
+        if had_errors {
+            return Err(format!("Cannot encode \"{text}\" as {}", self.name()));
+        }
+        Ok(bytes.into_owned())
+    }
+}
+
+// 用流式解码器解码一块数据，末尾不完整的字符留在解码器中
+pub fn decode_stream(decoder: &mut Decoder, bytes: &[u8]) -> String {
+    let capacity = decoder
+        .max_utf8_buffer_length(bytes.len())
+        .unwrap_or(bytes.len() * 3);
+    let mut out = String::with_capacity(capacity);
+    let _ = decoder.decode_to_string(bytes, &mut out, false);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_labels() {
+        assert!(Charset::parse(" UTF8 ").unwrap().is_utf8());
+        assert_eq!(Charset::parse("gbk").unwrap().name(), "GBK");
+        assert_eq!(Charset::parse("latin1").unwrap().name(), "windows-1252");
+        assert!(Charset::parse("klingon").is_err());
+    }
+
+    #[test]
+    fn reject_asymmetric_encodings() {
+        for label in ["utf-16le", "utf-16be", "iso-2022-kr", "hz-gb-2312"] {
+            assert!(Charset::parse(label).is_err(), "{label}");
+        }
+    }
+
+    #[test]
+    fn encode() {
+        let gbk = Charset::parse("gbk").unwrap();
+        assert_eq!(gbk.encode("中").unwrap(), [0xD6, 0xD0]);
+        assert_eq!(gbk.decode(&[0xD6, 0xD0]), "中");
+        let latin1 = Charset::parse("latin1").unwrap();
+        assert_eq!(
+            latin1.encode("中").unwrap_err(),
+            "Cannot encode \"中\" as windows-1252"
+        );
+    }
+
+    #[test]
+    fn stream_decoding() {
+        let mut decoder = Charset::default().decoder();
+        let bytes = "中文".as_bytes();
+        assert_eq!(decode_stream(&mut decoder, &bytes[..2]), "");
+        assert_eq!(decode_stream(&mut decoder, &bytes[2..4]), "中");
+        assert_eq!(decode_stream(&mut decoder, &bytes[4..]), "文");
+    }
+}
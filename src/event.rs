@@ -0,0 +1,63 @@
+use std::{sync::mpsc::Sender, thread, time::Duration};
+
+use chrono::{DateTime, Local};
+use crossterm::event::{self, Event};
+use serialport::SerialPortInfo;
+
+use crate::modem::ModemLines;
+
+// 后台重新枚举串口的间隔
+const PORT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// 主循环处理的所有事件：终端输入和后台串口线程的消息走同一个 channel，
+// 任意一方有新消息都会立刻唤醒 UI
+pub enum AppEvent {
+    Input(Event),
+    Serial(u64, SerialEvent), // 连接编号 + 事件，用于丢弃已关闭连接的残留消息
+    Ports(Vec<SerialPortInfo>), // 串口列表发生变化（插拔设备）
+}
+
+pub enum SerialEvent {
+    Data(Vec<u8>, DateTime<Local>), // 收到的一块数据及其到达时间
+    Sent {
+        data: Vec<u8>,
+        echo: Option<String>, // None 表示不回显
+        time: DateTime<Local>,
+    },
+    WriteFailed(String),
+    ControlFailed(String),   // 设置 DTR / RTS 失败
+    Break(Option<Duration>), // BREAK 已开始，附带到 break=off 为止的时长，None 表示序列里没有结束
+    Modem(ModemLines),       // 控制线状态变化
+    Disconnected(String),    // 读取出错，后台线程已退出
+}
+
+// 终端输入线程：阻塞读取按键等事件并转发到主循环
+pub fn spawn_input_thread(tx: Sender<AppEvent>) {
+    thread::spawn(move || {
+        while let Ok(event) = event::read() {
+            if tx.send(AppEvent::Input(event)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// 串口监视线程：定期枚举串口，列表有变化时通知主循环
+pub fn spawn_port_thread(tx: Sender<AppEvent>, mut ports: Vec<SerialPortInfo>) {
+    thread::spawn(move || {
+        loop {
+            thread::sleep(PORT_POLL_INTERVAL);
+            // 枚举偶尔会失败（如 udev 正在更新），跳过这一轮
+            let Ok(current) = serialport::available_ports() else {
+                continue;
+            };
+            if current == ports {
+                continue;
+            }
+            ports = current.clone();
+            if tx.send(AppEvent::Ports(current)).is_err() {
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,310 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use encoding_rs::Decoder;
+
+use crate::{
+    encoding::{Charset, decode_stream},
+    widgets::{HEX_ROW_BYTES, hex_row},
+};
+
+// 日志文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    Raw, // 原始字节
+    #[default]
+    Text, // 文本，每行带时间戳
+    Hex, // 带时间戳的十六进制转储
+}
+
+impl LogFormat {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "raw" => Ok(LogFormat::Raw),
+            "text" => Ok(LogFormat::Text),
+            "hex" => Ok(LogFormat::Hex),
+            _ => Err(format!("Unknown log format: {s} (raw/text/hex)")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogOptions {
+    pub path: PathBuf,
+    pub format: LogFormat,
+    pub include_tx: bool,
+    pub max_size: Option<u64>, // 单个文件的大小上限，超过后轮转
+    pub max_files: usize,      // 轮转时保留的旧文件个数（path.1 ... path.N）
+}
+
+impl LogOptions {
+    // 解析 log 命令参数：<path> [-f raw|text|hex] [-t] [-s 10M] [-n 5]
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let usage = || "Usage: log <path> [-f raw|text|hex] [-t] [-s size] [-n files] | log off";
+        let mut path = None;
+        let mut options = LogOptions {
+            path: PathBuf::new(),
+            format: LogFormat::default(),
+            include_tx: false,
+            max_size: None,
+            max_files: 5,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-f" => options.format = LogFormat::parse(args.next().ok_or_else(usage)?)?,
+                "-t" => options.include_tx = true,
+                "-s" => options.max_size = Some(parse_size(args.next().ok_or_else(usage)?)?),
+                "-n" => {
+                    let n = args.next().ok_or_else(usage)?;
+                    options.max_files =
+                        n.parse().map_err(|_| format!("Invalid file count: {n}"))?;
+                }
+                _ if path.is_none() && !arg.starts_with('-') => path = Some(PathBuf::from(arg)),
+                _ => return Err(format!("Unexpected argument: {arg}")),
+            }
+        }
+        options.path = path.ok_or_else(usage)?;
+        Ok(options)
+    }
+}
+
+// 解析 10M / 512K / 1G / 4096 这样的大小
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let upper = s.to_ascii_uppercase();
+    let trimmed = upper
+        .strip_suffix("IB")
+        .or_else(|| upper.strip_suffix('B'))
+        .unwrap_or(&upper);
+    let (digits, unit) = match trimmed.char_indices().last() {
+        Some((i, 'K')) => (&trimmed[..i], 1 << 10),
+        Some((i, 'M')) => (&trimmed[..i], 1 << 20),
+        Some((i, 'G')) => (&trimmed[..i], 1 << 30),
+        _ => (trimmed, 1),
+    };
+    // 乘上单位后溢出的也算非法
+    digits
+        .parse::<u64>()
+        .ok()
+        .filter(|&n| n > 0)
+        .and_then(|n| n.checked_mul(unit))
+        .ok_or_else(|| format!("Invalid size: {s}"))
+}
+
+// 人类可读的字节数，如 12.3 KiB
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+// 会话日志：把收发数据按选定格式写入文件，超过大小上限时轮转
+pub struct SessionLog {
+    options: LogOptions,
+    file: File,
+    file_size: u64, // 当前文件大小
+    written: u64,   // 本次会话累计写入的字节数
+    // text 格式：下一个接收字节是否位于行首；按接收字符集流式解码后以 UTF-8 写入
+    rx_line_start: bool,
+    decoder: Decoder,
+    // hex 格式：收发各自的流偏移
+    rx_offset: u64,
+    tx_offset: u64,
+}
+
+impl SessionLog {
+    pub fn open(options: LogOptions, charset: Charset) -> io::Result<Self> {
+        let file = open_append(&options.path)?;
+        let file_size = file.metadata()?.len();
+        Ok(Self {
+            options,
+            file,
+            file_size,
+            written: 0,
+            rx_line_start: true,
+            decoder: charset.decoder(),
+            rx_offset: 0,
+            tx_offset: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.options.path
+    }
+
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn set_charset(&mut self, charset: Charset) {
+        self.decoder = charset.decoder();
+    }
+
+    pub fn write_rx(&mut self, data: &[u8], time: DateTime<Local>) -> io::Result<()> {
+        let mut out = Vec::with_capacity(data.len() * 2);
+        match self.options.format {
+            LogFormat::Raw => out.extend_from_slice(data),
+            LogFormat::Text => {
+                let text = decode_stream(&mut self.decoder, data);
+                for piece in text.split_inclusive('\n') {
+                    if self.rx_line_start {
+                        out.extend_from_slice(text_prefix(time).as_bytes());
+                    }
+                    out.extend_from_slice(piece.as_bytes());
+                    self.rx_line_start = piece.ends_with('\n');
+                }
+            }
+            LogFormat::Hex => {
+                hex_dump(&mut out, "RX", data, self.rx_offset, time);
+                self.rx_offset += data.len() as u64;
+            }
+        }
+        self.write_record(&out)
+    }
+
+    // data 为实际发送的字节，echo 为接收区中显示的回显文本（终端模式的按键没有回显，
+    // text 格式下不记录）
+    pub fn write_tx(
+        &mut self,
+        data: &[u8],
+        echo: Option<&str>,
+        time: DateTime<Local>,
+    ) -> io::Result<()> {
+        if !self.options.include_tx {
+            return Ok(());
+        }
+        let mut out = Vec::new();
+        match self.options.format {
+            LogFormat::Raw => out.extend_from_slice(data),
+            LogFormat::Text => {
+                let Some(echo) = echo else {
+                    return Ok(());
+                };
+                // 发送记录单独占一行
+                if !self.rx_line_start {
+                    out.push(b'\n');
+                    self.rx_line_start = true;
+                }
+                out.extend_from_slice(format!("{}> {echo}\n", text_prefix(time)).as_bytes());
+            }
+            LogFormat::Hex => {
+                hex_dump(&mut out, "TX", data, self.tx_offset, time);
+                self.tx_offset += data.len() as u64;
+            }
+        }
+        self.write_record(&out)
+    }
+
+    fn write_record(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if let Some(max_size) = self.options.max_size
+            && self.file_size > 0
+            && self.file_size + bytes.len() as u64 > max_size
+        {
+            self.rotate()?;
+        }
+        self.file.write_all(bytes)?;
+        self.file_size += bytes.len() as u64;
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+
+    // path -> path.1 -> path.2 ...，超过 max_files 的最旧文件被删除
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |i: usize| {
+            let mut name = self.options.path.clone().into_os_string();
+            name.push(format!(".{i}"));
+            PathBuf::from(name)
+        };
+        let n = self.options.max_files;
+        if n == 0 {
+            fs::remove_file(&self.options.path)?;
+        } else {
+            if rotated(n).exists() {
+                fs::remove_file(rotated(n))?;
+            }
+            for i in (1..n).rev() {
+                if rotated(i).exists() {
+                    fs::rename(rotated(i), rotated(i + 1))?;
+                }
+            }
+            fs::rename(&self.options.path, rotated(1))?;
+        }
+        self.file = open_append(&self.options.path)?;
+        self.file_size = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn text_prefix(time: DateTime<Local>) -> String {
+    format!("[{}] ", time.format("%Y-%m-%d %H:%M:%S%.3f"))
+}
+
+fn hex_dump(out: &mut Vec<u8>, dir: &str, data: &[u8], offset: u64, time: DateTime<Local>) {
+    let time = time.format("%H:%M:%S%.3f");
+    for (row, chunk) in data.chunks(HEX_ROW_BYTES).enumerate() {
+        let (hex, ascii) = hex_row(chunk);
+        let offset = offset + (row * HEX_ROW_BYTES) as u64;
+        out.extend_from_slice(
+            format!("{time} {dir} {offset:08x}: {hex:<39}  {ascii}\n").as_bytes(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert_eq!(parse_size("512K").unwrap(), 512 << 10);
+        assert_eq!(parse_size("10m").unwrap(), 10 << 20);
+        assert_eq!(parse_size("1GiB").unwrap(), 1 << 30);
+        assert_eq!(parse_size("2KB").unwrap(), 2048);
+        assert_eq!(parse_size("7b").unwrap(), 7);
+    }
+
+    #[test]
+    fn invalid_sizes() {
+        for s in ["", "0", "0K", "K", "-1M", "1.5M", "10T", "M10", "1 M"] {
+            assert_eq!(
+                parse_size(s).unwrap_err(),
+                format!("Invalid size: {s}"),
+                "{s:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn size_overflow() {
+        assert_eq!(parse_size("17179869183G").unwrap(), 17179869183 << 30);
+        assert!(parse_size("17179869184G").is_err());
+        assert!(parse_size("18446744073709551616").is_err());
+    }
+
+    #[test]
+    fn format_sizes() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(10 << 20), "10.0 MiB");
+        assert_eq!(format_size(u64::MAX), "17179869184.0 GiB");
+    }
+}
@@ -1,95 +1,658 @@
-use std::time::Duration;
+use std::{
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    time::{Duration, Instant},
+};
 
 use color_eyre::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    execute,
+};
 use ratatui::{
     DefaultTerminal, Frame,
     layout::{Constraint, Layout},
+    text::Line,
+    widgets::{Block, Paragraph},
 };
+use serialport::{SerialPortInfo, SerialPortType};
 mod action;
 mod command;
+mod connection;
+mod encoding;
+mod event;
+mod logger;
+mod modem;
+mod serial;
 mod widgets;
 use action::*;
+use command::{LineEnding, default_highlight_config, encode_hex_parts, load_highlight_rules};
+use encoding::Charset;
+use modem::{LineStep, ModemLines, ResetSequence, builtin_sequences};
+use widgets::AnsiMode;
 mod components;
 use components::*;
-use serialport::SerialPort;
+use connection::*;
+use event::*;
+use logger::*;
+use serial::*;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     UartChoice,
     RateChoice,
+    DataBitsChoice,
+    ParityChoice,
+    StopBitsChoice,
+    FlowControlChoice,
     CommandInput,
+    SendInput, // 发送模式：输入框中的每一行直接写入串口
+    Receive,   // 焦点在接收区，可滚动查看历史数据
+    Terminal,  // 终端模式：按键直接发给串口，接收区作为 VT100 屏幕
+}
+
+impl Mode {
+    // 状态栏中显示的模式名
+    pub fn label(&self) -> &'static str {
+        match self {
+            Mode::UartChoice => "选择串口",
+            Mode::RateChoice => "选择波特率",
+            Mode::DataBitsChoice => "选择数据位",
+            Mode::ParityChoice => "选择校验位",
+            Mode::StopBitsChoice => "选择停止位",
+            Mode::FlowControlChoice => "选择流控",
+            Mode::CommandInput => "命令",
+            Mode::SendInput => "发送",
+            Mode::Receive => "浏览",
+            Mode::Terminal => "终端",
+        }
+    }
+}
+
+// 每帧最多花多少时间处理积压的事件，之后先重绘一次
+const FRAME_BUDGET: Duration = Duration::from_millis(16);
+// 断线后尝试重新打开串口的间隔
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+// 断线后等待重新连接的设备：有 USB 序列号时按序列号查找（重新插入后设备名可能变化），
+// 否则按路径查找
+struct Reconnect {
+    path: String,
+    serial: Option<String>,
+    next_attempt: Instant,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
-    ratatui::run(app)?;
+    // 崩溃时也要关闭鼠标捕获，否则终端之后会一直输出鼠标转义序列
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = execute!(std::io::stdout(), DisableMouseCapture);
+        hook(info);
+    }));
+    // 开启鼠标捕获以支持滚轮滚动接收区
+    execute!(std::io::stdout(), EnableMouseCapture)?;
+    let result = ratatui::run(app);
+    execute!(std::io::stdout(), DisableMouseCapture)?;
+    result?;
     Ok(())
 }
 struct App {
-    com: String,
+    com: String,                // 选中的串口，没有串口时为空
+    ports: Vec<SerialPortInfo>, // 最近一次枚举到的串口
     rate: u32,
-    port: Option<Box<dyn SerialPort>>,
+    settings: LineSettings,
+    line_ending: LineEnding,
+    charset: Charset, // 收发文本使用的字符集
+    port: Option<SerialConnection>,
+    port_serial: Option<String>, // 已打开串口的 USB 序列号，用于断线后找回同一设备
+    auto_reconnect: bool,
+    reconnect: Option<Reconnect>, // 正在等待重新连接
+    modem: Option<ModemLines>,    // 串口线程上报的控制线状态
+    reset_sequences: Vec<ResetSequence>,
+    events: Sender<AppEvent>, // 交给后台串口线程，用于回传数据
+    next_conn_id: u64,
+    log: Option<SessionLog>,
     should_quit: bool,
     mode: Mode,
     // 实例化组件
     uart_list: ListComponent,
     rate_list: ListComponent,
+    data_bits_list: ListComponent,
+    parity_list: ListComponent,
+    stop_bits_list: ListComponent,
+    flow_control_list: ListComponent,
     input: CommandInputComponent,
     receive_area: ReceiveComponent,
+    status_bar: StatusBarComponent,
+    notifications: NotificationComponent,
 }
 
 impl App {
-    fn new() -> Self {
+    fn new(events: Sender<AppEvent>, ports: Vec<SerialPortInfo>) -> Self {
         // 初始化逻辑
-        let ports: Vec<String> = serialport::available_ports()
-            .map(|p| p.iter().map(|x| x.port_name.clone()).collect())
-            .unwrap_or_default();
+        let labels: Vec<String> = ports.iter().map(port_label).collect();
 
-        Self {
-            com: ports[0].clone(),
+        let mut app = Self {
+            com: ports
+                .first()
+                .map(|p| p.port_name.clone())
+                .unwrap_or_default(),
+            ports,
             rate: 9600,
+            settings: LineSettings::default(),
+            line_ending: LineEnding::default(),
+            charset: Charset::default(),
             port: None,
+            port_serial: None,
+            auto_reconnect: true,
+            reconnect: None,
+            modem: None,
+            reset_sequences: builtin_sequences(),
+            events,
+            next_conn_id: 0,
+            log: None,
             should_quit: false,
             mode: Mode::CommandInput, // 默认模式
             uart_list: ListComponent::new(
                 "端口号".to_string(),
-                ports,
+                labels,
                 Action::SelectPort, // 闭包：决定选中后产生什么 Action
             ),
             rate_list: ListComponent::new(
                 "波特率".to_string(),
-                vec!["9600".into(), "115200".into()],
+                BAUD_RATES.map(|r| r.to_string()).to_vec(),
                 Action::SelectRate,
             ),
+            data_bits_list: ListComponent::new(
+                "数据位".to_string(),
+                DATA_BITS_ITEMS.map(String::from).to_vec(),
+                Action::SelectDataBits,
+            ),
+            parity_list: ListComponent::new(
+                "校验位".to_string(),
+                PARITY_ITEMS.map(String::from).to_vec(),
+                Action::SelectParity,
+            ),
+            stop_bits_list: ListComponent::new(
+                "停止位".to_string(),
+                STOP_BITS_ITEMS.map(String::from).to_vec(),
+                Action::SelectStopBits,
+            ),
+            flow_control_list: ListComponent::new(
+                "流控".to_string(),
+                FLOW_CONTROL_ITEMS.map(String::from).to_vec(),
+                Action::SelectFlowControl,
+            ),
             input: CommandInputComponent::new(),
             receive_area: ReceiveComponent::new(),
+            status_bar: StatusBarComponent::new(),
+            notifications: NotificationComponent::new(),
+        };
+        app.sync_setting_lists();
+        if let Some(path) = default_highlight_config().filter(|p| p.exists()) {
+            app.update(Action::LoadHighlights(path));
         }
+        app
     }
 
     // 统一更新逻辑
     fn update(&mut self, action: Action) {
         match action {
             Action::Quit => self.should_quit = true,
-            Action::SwitchMode(mode) => self.mode = mode,
-            Action::SelectPort(port) => {
-                self.com = port.clone();
+            Action::SwitchMode(mode) => {
+                if mode == Mode::Terminal && self.port.is_none() {
+                    return self.update(Action::Error("Port is not open".to_string()));
+                }
+                self.mode = mode;
+                self.input.set_send_mode(mode == Mode::SendInput);
+                self.receive_area.state.set_terminal(mode == Mode::Terminal);
+            }
+            Action::SelectPort(spec) => {
                 self.mode = Mode::CommandInput;
+                match find_port(&self.ports, &spec) {
+                    Ok(name) => {
+                        if name == self.com {
+                            return;
+                        }
+                        self.reconnect = None; // 换了串口，不再等待原来的设备
+                        // 已连接时切换串口：先关闭原来的连接，再用相同参数打开新串口
+                        let switch = self.port.is_some();
+                        if switch {
+                            self.close_port();
+                        }
+                        self.com = name;
+                        self.select_com();
+                        if switch {
+                            self.update(Action::Open);
+                        }
+                    }
+                    Err(e) => self.update(Action::Error(e)),
+                }
             }
             Action::SelectRate(rate) => {
                 // 设置波特率逻辑
-                self.rate = rate.parse().unwrap();
                 self.mode = Mode::CommandInput;
+                let rate = match parse_baud_rate(&rate) {
+                    Ok(rate) => rate,
+                    Err(e) => {
+                        self.sync_setting_lists();
+                        return self.update(Action::Error(e));
+                    }
+                };
+                // 串口已打开时先让驱动应用，失败则保留原波特率，列表也选回原来的值
+                if let Some(conn) = &mut self.port
+                    && let Err(e) = conn.port.set_baud_rate(rate)
+                {
+                    self.sync_setting_lists();
+                    return self.update(Action::Error(format!(
+                        "Baud rate {rate} not supported: {e}"
+                    )));
+                }
+                self.rate = rate;
+                self.rate_list.state.select_item(&rate.to_string());
+            }
+            Action::SelectDataBits(v) => self.set_line_setting(parse_data_bits(&v), |s, v| {
+                s.data_bits = v;
+            }),
+            Action::SelectParity(v) => {
+                self.set_line_setting(parse_parity(&v), |s, v| s.parity = v);
             }
-            Action::Error(e) => {
-                // 可以在这里设置一个错误提示弹窗的状态
+            Action::SelectStopBits(v) => self.set_line_setting(parse_stop_bits(&v), |s, v| {
+                s.stop_bits = v;
+            }),
+            Action::SelectFlowControl(v) => {
+                self.set_line_setting(parse_flow_control(&v), |s, v| s.flow_control = v);
             }
+            Action::Error(e) => self.notifications.push(Level::Error, e),
+            Action::Notify(level, text) => self.notifications.push(level, text),
+            Action::ShowMessages => self.notifications.open_history(),
             Action::None => {}
             Action::Open => {
-                self.port = serialport::new(self.com.clone(), self.rate)
-                    .timeout(Duration::from_millis(1)) // 超时设置
-                    .open()
-                    .ok();
+                if self.com.is_empty() {
+                    return self.update(Action::Error(
+                        "No port selected, plug in a device and run refresh".to_string(),
+                    ));
+                }
+                if self.port.is_some() {
+                    return self.update(Action::Error(format!(
+                        "Port {} is already open, use reopen to apply a new port or close it first",
+                        self.com
+                    )));
+                }
+                match self.open_port() {
+                    Ok(()) => {
+                        self.reconnect = None;
+                        self.status_bar.reset_counters();
+                        let note = format!(
+                            "已打开串口 {} {} {}",
+                            self.com,
+                            self.rate,
+                            self.settings.summary()
+                        );
+                        self.receive_area.state.append_note(&note);
+                    }
+                    Err(e) => self.update(Action::Error(e)),
+                }
+            }
+            Action::Close => {
+                if self.port.is_none() && self.reconnect.is_none() {
+                    return self.update(Action::Error("Port is not open".to_string()));
+                }
+                self.close_port();
+                self.receive_area
+                    .state
+                    .append_note(&format!("已关闭串口 {}", self.com));
+            }
+            Action::Reopen => {
+                self.close_port();
+                self.update(Action::Open);
+            }
+            Action::SetLines(steps) => match &self.port {
+                Some(conn) => conn.control(steps),
+                None => self.update(Action::Error("Port is not open".to_string())),
+            },
+            // 标记由后台线程在 BREAK 真正开始后通过 SerialEvent::Break 补上
+            Action::SendBreak(duration) => self.update(Action::SetLines(vec![
+                LineStep::Break(true),
+                LineStep::Wait(duration),
+                LineStep::Break(false),
+            ])),
+            Action::RunReset(name) => {
+                let Some(seq) = self.reset_sequences.iter().find(|s| s.name == name) else {
+                    return self.update(Action::Error(format!(
+                        "Unknown reset sequence: {name} (see reset list)"
+                    )));
+                };
+                let (note, steps) = (format!("复位序列 {}", seq.describe()), seq.steps.clone());
+                if self.port.is_some() {
+                    self.receive_area.state.append_note(&note);
+                }
+                self.update(Action::SetLines(steps));
+            }
+            Action::DefineReset(seq) => {
+                self.reset_sequences.retain(|s| s.name != seq.name);
+                self.reset_sequences.push(seq);
+            }
+            Action::ListResets => {
+                for seq in &self.reset_sequences {
+                    self.receive_area
+                        .state
+                        .append_note(&format!("复位序列 {}", seq.describe()));
+                }
+            }
+            Action::SetReconnect(enabled) => {
+                self.auto_reconnect = enabled;
+                if !enabled {
+                    self.reconnect = None;
+                }
             }
+            Action::RefreshPorts => match serialport::available_ports() {
+                Ok(ports) => {
+                    let count = ports.len();
+                    self.set_ports(ports);
+                    self.update(Action::Notify(Level::Info, format!("找到 {count} 个串口")));
+                }
+                Err(e) => self.update(Action::Error(format!("Failed to list ports: {e}"))),
+            },
+            Action::SetLineEnding(ending) => self.line_ending = ending,
+            Action::SetNewline(mode) => self.receive_area.state.set_newline(mode),
+            Action::SetCharset(charset) => {
+                self.charset = charset;
+                self.receive_area.state.set_charset(charset);
+                if let Some(log) = &mut self.log {
+                    log.set_charset(charset);
+                }
+            }
+            Action::SetView(view) => self.receive_area.state.set_view(view),
+            Action::SetTimestamp(mode) => {
+                let state = &mut self.receive_area.state;
+                state.set_timestamp(mode.unwrap_or_else(|| state.timestamp().next()));
+            }
+            Action::SetAnsi(mode) => {
+                let state = &mut self.receive_area.state;
+                // 不带参数时在解析和原样显示之间切换，方便调试
+                let mode = mode.unwrap_or(match state.ansi() {
+                    AnsiMode::Literal => AnsiMode::Render,
+                    _ => AnsiMode::Literal,
+                });
+                state.set_ansi(mode);
+            }
+            Action::StartLog(options) => {
+                let path = options.path.display().to_string();
+                match SessionLog::open(options, self.charset) {
+                    Ok(log) => {
+                        self.log = Some(log);
+                        self.update(Action::Notify(Level::Info, format!("开始记录日志 {path}")));
+                    }
+                    Err(e) => self.update(Action::Error(format!("Failed to open log {path}: {e}"))),
+                }
+            }
+            Action::StopLog => {
+                if let Some(log) = self.log.take() {
+                    let text = format!("日志已保存到 {}", log.path().display());
+                    self.update(Action::Notify(Level::Info, text));
+                }
+            }
+            Action::SetScrollback(n) => self.receive_area.state.set_max_lines(n),
+            // 搜索随输入实时执行，表达式还没输完时常常无效，错误只显示在输入框标题里
+            Action::Search(query) => {
+                let result = self.receive_area.state.set_search(&query);
+                self.input.set_search_error(result.err());
+            }
+            Action::SetSearchRegex(regex) => {
+                if let Err(e) = self.receive_area.state.set_search_regex(regex) {
+                    self.update(Action::Error(e));
+                }
+            }
+            Action::AddHighlight(rule) => self.receive_area.state.add_rule(rule),
+            Action::ListHighlights => {
+                let state = &mut self.receive_area.state;
+                let notes: Vec<String> = if state.rules().is_empty() {
+                    vec!["没有高亮规则".to_string()]
+                } else {
+                    state
+                        .rules()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, rule)| format!("高亮规则 {}: {}", i + 1, rule.describe()))
+                        .collect()
+                };
+                for note in notes {
+                    state.append_note(&note);
+                }
+            }
+            Action::RemoveHighlight(n) => {
+                if let Err(e) = self.receive_area.state.remove_rule(n) {
+                    self.update(Action::Error(e));
+                }
+            }
+            Action::LoadHighlights(path) => match load_highlight_rules(&path) {
+                Ok(rules) => {
+                    let state = &mut self.receive_area.state;
+                    let count = rules.len();
+                    for rule in rules {
+                        state.add_rule(rule);
+                    }
+                    state.append_note(&format!("已从 {} 加载 {count} 条高亮规则", path.display()));
+                }
+                Err(e) => self.update(Action::Error(e)),
+            },
+            Action::SetFilter(pattern, invert) => {
+                if let Err(e) = self.receive_area.state.set_filter(&pattern, invert) {
+                    self.update(Action::Error(e));
+                }
+            }
+            Action::ClearFilter => self.receive_area.state.clear_filter(),
+            Action::Send(text) => {
+                let mut data = match self.charset.encode(&text) {
+                    Ok(data) => data,
+                    Err(e) => return self.update(Action::Error(e)),
+                };
+                data.extend_from_slice(self.line_ending.as_bytes());
+                self.write_port(&data, Some(&text));
+            }
+            Action::SendHex(parts) => {
+                let bytes = match encode_hex_parts(&parts, self.charset) {
+                    Ok(bytes) => bytes,
+                    Err(e) => return self.update(Action::Error(e)),
+                };
+                let echo = bytes
+                    .iter()
+                    .map(|b| format!("{b:02X}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                self.write_port(&bytes, Some(&echo));
+            }
+            Action::SendRaw(bytes) => self.write_port(&bytes, None),
+        }
+    }
+
+    // 修改一项线路参数：解析失败报错，串口已打开时立即生效
+    fn set_line_setting<T>(
+        &mut self,
+        value: Result<T, String>,
+        set: impl FnOnce(&mut LineSettings, T),
+    ) {
+        self.mode = Mode::CommandInput;
+        let value = match value {
+            Ok(v) => v,
+            Err(e) => return self.update(Action::Error(e)),
+        };
+        // 先在副本上修改并应用到串口，成功后才保存，界面不会显示串口实际没有的参数
+        let mut settings = self.settings;
+        set(&mut settings, value);
+        if let Some(conn) = &mut self.port
+            && let Err(e) = settings.apply(conn.port.as_mut())
+        {
+            // 可能只应用了一部分，用原来的参数恢复
+            let restored = self.settings.apply(conn.port.as_mut());
+            let mut message = format!("Failed to apply settings: {e}");
+            if let Err(e) = restored {
+                message.push_str(&format!(", restoring previous settings also failed: {e}"));
+            }
+            self.sync_setting_lists();
+            return self.update(Action::Error(message));
+        }
+        self.settings = settings;
+        self.sync_setting_lists();
+    }
+
+    // 串口列表变化：更新端口列表（保持选中项），并提示当前串口被拔出或重新插入
+    fn set_ports(&mut self, ports: Vec<SerialPortInfo>) {
+        let present =
+            |list: &[SerialPortInfo], name: &str| list.iter().any(|p| p.port_name == name);
+        let was_present = present(&self.ports, &self.com);
+        let is_present = present(&ports, &self.com);
+        self.uart_list
+            .state
+            .update_items(ports.iter().map(port_label).collect());
+        self.ports = ports;
+
+        if self.com.is_empty() {
+            // 启动时没有串口，插入第一个设备后自动选中
+            if let Some(port) = self.selected_port() {
+                self.com = port.port_name.clone();
+            }
+            return;
+        }
+        if self.reconnect.is_some() {
+            return; // 断线重连会自己提示
+        }
+        let note = match (was_present, is_present) {
+            (true, false) => format!("串口 {} 已移除", self.com),
+            (false, true) => {
+                self.select_com();
+                format!("串口 {} 已重新插入", self.com)
+            }
+            _ => return,
+        };
+        self.receive_area.state.append_note(&note);
+    }
+
+    // 关闭当前连接（后台线程随之退出），并停止等待重新连接
+    fn close_port(&mut self) {
+        self.port = None;
+        self.modem = None;
+        self.reconnect = None;
+        if self.mode == Mode::Terminal {
+            self.update(Action::SwitchMode(Mode::CommandInput));
+        }
+    }
+
+    // 按当前参数打开 com，成功后记下设备的 USB 序列号
+    fn open_port(&mut self) -> Result<(), String> {
+        let conn = serialport::new(self.com.clone(), self.rate)
+            .data_bits(self.settings.data_bits)
+            .parity(self.settings.parity)
+            .stop_bits(self.settings.stop_bits)
+            .flow_control(self.settings.flow_control)
+            .timeout(READ_TIMEOUT) // 超时设置
+            .open()
+            .and_then(|port| {
+                self.next_conn_id += 1;
+                SerialConnection::start(port, self.next_conn_id, self.events.clone())
+            })
+            .map_err(|e| open_error_message(&self.com, self.rate, &e))?;
+        self.port = Some(conn);
+        self.modem = None;
+        self.port_serial = self
+            .ports
+            .iter()
+            .find(|p| p.port_name == self.com)
+            .and_then(|p| match &p.port_type {
+                SerialPortType::UsbPort(usb) => usb.serial_number.clone(),
+                _ => None,
+            });
+        Ok(())
+    }
+
+    // 定时调用：等待重新连接时，设备重新出现后用原来的参数打开
+    fn tick(&mut self) {
+        let Some(reconnect) = &mut self.reconnect else {
+            return;
+        };
+        let now = Instant::now();
+        if now < reconnect.next_attempt {
+            return;
+        }
+        reconnect.next_attempt = now + RECONNECT_INTERVAL;
+        let found = match &reconnect.serial {
+            Some(serial) => self
+                .ports
+                .iter()
+                .find(|p| {
+                    matches!(&p.port_type, SerialPortType::UsbPort(usb)
+                        if usb.serial_number.as_ref() == Some(serial))
+                })
+                .map(|p| p.port_name.clone()),
+            // 不在枚举列表中的设备（如虚拟串口）直接看路径是否存在
+            None => Some(reconnect.path.clone()).filter(|path| {
+                self.ports.iter().any(|p| &p.port_name == path)
+                    || std::path::Path::new(path).exists()
+            }),
+        };
+        let Some(path) = found else {
+            return;
+        };
+        self.com = path;
+        self.select_com();
+        // 刚插入时设备可能还没准备好（权限、驱动），失败了下一轮再试
+        if self.open_port().is_ok() {
+            self.reconnect = None;
+            let text = format!("串口 {} 已重新连接", self.com);
+            self.receive_area.state.append_note(&text);
+            self.update(Action::Notify(Level::Info, text));
+        }
+    }
+
+    // 串口列表中光标所在的串口
+    fn selected_port(&self) -> Option<&SerialPortInfo> {
+        self.uart_list
+            .state
+            .selected()
+            .and_then(|i| self.ports.get(i))
+    }
+
+    // 让串口列表的选中项与 com 一致
+    fn select_com(&mut self) {
+        if let Some(port) = self.ports.iter().find(|p| p.port_name == self.com) {
+            self.uart_list.state.select_item(&port_label(port));
+        }
+    }
+
+    // 让各参数列表的选中项与当前设置一致
+    fn sync_setting_lists(&mut self) {
+        let s = self.settings;
+        self.rate_list.state.select_item(&self.rate.to_string());
+        self.data_bits_list
+            .state
+            .select_item(&u8::from(s.data_bits).to_string());
+        self.parity_list
+            .state
+            .select_item(&s.parity.to_string().to_lowercase());
+        self.stop_bits_list
+            .state
+            .select_item(&u8::from(s.stop_bits).to_string());
+        self.flow_control_list
+            .state
+            .select_item(&s.flow_control.to_string().to_lowercase());
+    }
+
+    // 交给后台线程写入串口，写成功后（SerialEvent::Sent）再把 echo 回显到接收区
+    fn write_port(&mut self, data: &[u8], echo: Option<&str>) {
+        let Some(conn) = &self.port else {
+            self.update(Action::Error("Port is not open".to_string()));
+            return;
+        };
+        conn.write(data.to_vec(), echo.map(str::to_string));
+    }
+
+    // 日志写入失败时停止记录，避免每次收发都重复报错
+    fn check_log(&mut self, result: std::io::Result<()>) {
+        if let Err(e) = result {
+            self.log = None;
+            self.update(Action::Error(format!(
+                "Log write failed, logging stopped: {e}"
+            )));
         }
     }
 
@@ -98,7 +661,12 @@ impl App {
         match self.mode {
             Mode::UartChoice => &mut self.uart_list,
             Mode::RateChoice => &mut self.rate_list,
-            Mode::CommandInput => &mut self.input,
+            Mode::DataBitsChoice => &mut self.data_bits_list,
+            Mode::ParityChoice => &mut self.parity_list,
+            Mode::StopBitsChoice => &mut self.stop_bits_list,
+            Mode::FlowControlChoice => &mut self.flow_control_list,
+            Mode::CommandInput | Mode::SendInput => &mut self.input,
+            Mode::Receive | Mode::Terminal => &mut self.receive_area,
         }
     }
 
@@ -109,16 +677,26 @@ impl App {
         // 你原本的代码在 UartChoice/RateChoice 时似乎想把 command 压缩？
         // 这里我根据你的逻辑还原：
         // 如果你需要根据模式改变底部输入框的高度，可以在这里 match self.mode
-        let main_constraints = [Constraint::Fill(1), Constraint::Length(3)];
+        let main_constraints = [
+            Constraint::Fill(1),
+            Constraint::Length(3),
+            Constraint::Length(1), // 状态栏
+        ];
 
         let main_layout = Layout::vertical(main_constraints).split(area);
         let up_area = main_layout[0];
         let command_area = main_layout[1];
+        let status_area = main_layout[2];
 
         // 2. 上半部分布局：左边是列表，右边是接收区
         let hor_layout = Layout::horizontal([
-            Constraint::Length(20), // 左侧面板宽度
-            Constraint::Fill(1),    // 右侧接收区
+            // 左侧面板宽度，选择串口时加宽以显示 USB 设备信息
+            Constraint::Length(if self.mode == Mode::UartChoice {
+                48
+            } else {
+                20
+            }),
+            Constraint::Fill(1), // 右侧接收区
         ])
         .split(up_area);
 
@@ -126,97 +704,287 @@ impl App {
         let receive_data_area = hor_layout[1]; // 暂时没用到，留给未来
 
         // 3. 左侧面板布局：这是你最关心的动态部分
-        // 根据当前模式，决定 串口列表 和 波特率列表 的高度比例
-        let left_constraints = match self.mode {
-            Mode::UartChoice => {
-                *self.uart_list.state.state().offset_mut() = 0;
-                [
-                    Constraint::Fill(1),   // 串口列表占满
-                    Constraint::Length(0), // 波特率列表隐藏 (或者设为 Min(1) 显示一点点)
-                ]
-            }
-            Mode::RateChoice => {
-                *self.rate_list.state.state().offset_mut() = 0;
-                [
-                    Constraint::Length(0), // 串口列表隐藏
-                    Constraint::Fill(1),   // 波特率列表占满
-                ]
-            }
-            Mode::CommandInput => [
-                Constraint::Length(3), // 默认均分，或者按需分配
-                Constraint::Length(3),
-            ],
-        };
+        // 选择模式下对应的列表占满左侧，其余隐藏；
+        // 输入模式下显示 串口 / 波特率 两个列表和参数摘要
+        let mode = self.mode;
+        let lists: [(Mode, &mut ListComponent); 6] = [
+            (Mode::UartChoice, &mut self.uart_list),
+            (Mode::RateChoice, &mut self.rate_list),
+            (Mode::DataBitsChoice, &mut self.data_bits_list),
+            (Mode::ParityChoice, &mut self.parity_list),
+            (Mode::StopBitsChoice, &mut self.stop_bits_list),
+            (Mode::FlowControlChoice, &mut self.flow_control_list),
+        ];
+        let is_input = matches!(
+            mode,
+            Mode::CommandInput | Mode::SendInput | Mode::Receive | Mode::Terminal
+        );
+        let mut left_constraints: Vec<Constraint> = lists
+            .iter()
+            .map(|(list_mode, _)| {
+                if *list_mode == mode {
+                    Constraint::Fill(1)
+                } else if is_input && matches!(list_mode, Mode::UartChoice | Mode::RateChoice) {
+                    Constraint::Length(3)
+                } else {
+                    Constraint::Length(0)
+                }
+            })
+            .collect();
+        // 参数摘要：线路参数、字符集和接收断行方式
+        left_constraints.push(Constraint::Length(if is_input { 5 } else { 0 }));
+        // 选择串口时在列表下方显示选中串口的详情
+        left_constraints.push(Constraint::Length(if mode == Mode::UartChoice {
+            8
+        } else {
+            0
+        }));
 
         let left_layout = Layout::vertical(left_constraints).split(left_panel_area);
-        let uart_area = left_layout[0];
-        let rate_area = left_layout[1];
 
         // 4. 渲染组件
         // render 方法签名：fn render(&mut self, f: &mut Frame, area: Rect, is_active: bool)
+        for (i, (list_mode, list)) in lists.into_iter().enumerate() {
+            if list_mode == mode {
+                *list.state.state().offset_mut() = 0;
+            }
+            list.render(frame, left_layout[i], list_mode == mode);
+        }
 
-        // 渲染串口列表
-        self.uart_list
-            .render(frame, uart_area, self.mode == Mode::UartChoice);
+        let summary = vec![
+            Line::raw(format!("{} {}", self.rate, self.settings.summary())),
+            Line::raw(self.charset.name()),
+            Line::raw(format!(
+                "RX 换行 {}",
+                self.receive_area.state.newline().label()
+            )),
+        ];
+        frame.render_widget(
+            Paragraph::new(summary).block(Block::bordered().title("参数")),
+            left_layout[6],
+        );
 
-        // 渲染波特率列表
-        self.rate_list
-            .render(frame, rate_area, self.mode == Mode::RateChoice);
+        if mode == Mode::UartChoice {
+            let details: Vec<Line> = match self.selected_port() {
+                Some(port) => port_details(port).into_iter().map(Line::raw).collect(),
+                None => vec![Line::raw("没有串口，插入设备后自动刷新")],
+            };
+            frame.render_widget(
+                Paragraph::new(details).block(Block::bordered().title("端口详情")),
+                left_layout[7],
+            );
+        }
 
         // 渲染输入框
-        self.input
-            .render(frame, command_area, self.mode == Mode::CommandInput);
+        self.input.render(
+            frame,
+            command_area,
+            matches!(self.mode, Mode::CommandInput | Mode::SendInput),
+        );
+
+        // 标题栏右侧的状态：等待重新连接的设备、正在写入的日志文件和已写入字节数
+        let status: Vec<String> = [
+            self.reconnect
+                .as_ref()
+                .map(|r| format!("⟳ 等待 {} 重新连接", r.path)),
+            self.log.as_ref().map(|log| {
+                format!(
+                    "● LOG {} {}",
+                    log.path().display(),
+                    format_size(log.written())
+                )
+            }),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        self.receive_area
+            .state
+            .set_status((!status.is_empty()).then(|| status.join("  ")));
+
+        let state = if self.port.is_some() {
+            ConnectionState::Open
+        } else if self.reconnect.is_some() {
+            ConnectionState::Reconnecting
+        } else {
+            ConnectionState::Closed
+        };
+        let filter =
+            self.receive_area.state.filter().map(|(pattern, invert)| {
+                format!("{}\"{pattern}\"", if invert { "-v " } else { "" })
+            });
+        self.status_bar.set_info(StatusInfo {
+            port: self.com.clone(),
+            settings: format!("{} {}", self.rate, self.settings.summary()),
+            state,
+            mode: self.mode,
+            log: self
+                .log
+                .as_ref()
+                .and_then(|log| log.path().file_name())
+                .map(|name| name.to_string_lossy().into_owned()),
+            filter,
+        });
+        self.status_bar.render(frame, status_area, false);
+
+        // 串口打开时在接收区下方显示控制线状态条
+        let [receive_data_area, modem_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(u16::from(self.modem.is_some())),
+        ])
+        .areas(receive_data_area);
+        if let Some(modem) = &self.modem {
+            frame.render_widget(Paragraph::new(modem.strip()), modem_area);
+        }
 
         // 如果有接收区组件，也在这里渲染
-        self.receive_area.render(frame, receive_data_area, false);
-    }
-
-    fn try_read_serial_data(&mut self) -> Result<()> {
-        if let Some(port) = &mut self.port {
-            let mut buffer = [0u8; 256]; // 一次最多读 256 字节
-            match port.read(&mut buffer) {
-                Ok(n) if n > 0 => {
-                    let data = String::from_utf8_lossy(&buffer[..n]);
-                    self.receive_area.state.append_text(&data);
-                }
-                Ok(_) => {} // 读到 0 字节（无数据）
-                Err(e) => {
-                    // 可能是超时（正常），也可能是断开
-                    if e.kind() != std::io::ErrorKind::TimedOut {
-                        // 真实错误：串口断开？
-                        eprintln!("串口读取错误: {}", e);
-                        self.port = None; // 关闭串口
-                    }
+        self.receive_area.render(
+            frame,
+            receive_data_area,
+            matches!(self.mode, Mode::Receive | Mode::Terminal),
+        );
+
+        // 提示消息和弹窗画在最上层
+        self.notifications.render(frame, area, true);
+    }
+
+    fn handle_event(&mut self, event: AppEvent) -> Result<()> {
+        match event {
+            AppEvent::Input(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                // 错误弹窗和消息记录打开时按键只交给它们
+                if self.notifications.is_modal() {
+                    let action = self.notifications.handle_key_events(key)?;
+                    self.update(action);
+                    return Ok(());
                 }
+                // 全局快捷键处理 (比如 : 键)，终端模式下 Esc 要发给串口
+                if key.code == KeyCode::Esc && self.mode != Mode::Terminal {
+                    self.update(Action::SwitchMode(Mode::CommandInput));
+                    return Ok(());
+                }
+
+                // 输入时也可以直接用 PgUp/PgDn 翻看接收区
+                let action = if matches!(key.code, KeyCode::PageUp | KeyCode::PageDown)
+                    && matches!(self.mode, Mode::CommandInput | Mode::SendInput)
+                {
+                    self.receive_area.handle_key_events(key)?
+                } else {
+                    // 3. 将事件派发给当前活跃的组件
+                    self.get_active_component_mut().handle_key_events(key)?
+                };
+
+                // 4. App 处理组件返回的 Action
+                self.update(action);
+            }
+            // 滚轮总是滚动接收区，不需要先切换焦点
+            AppEvent::Input(Event::Mouse(mouse)) => {
+                let action = self.receive_area.handle_mouse_events(mouse)?;
+                self.update(action);
             }
+            AppEvent::Input(_) => {} // 窗口大小变化等，下一帧重绘即可
+            AppEvent::Serial(id, event) => self.handle_serial_event(id, event),
+            AppEvent::Ports(ports) => self.set_ports(ports),
         }
         Ok(())
     }
+
+    // 处理后台串口线程发来的事件
+    fn handle_serial_event(&mut self, id: u64, event: SerialEvent) {
+        // 已经关闭或被替换的连接遗留的消息直接丢弃
+        if self.port.as_ref().is_none_or(|conn| conn.id() != id) {
+            return;
+        }
+        match event {
+            SerialEvent::Data(data, time) => {
+                self.status_bar.add_rx(data.len());
+                self.receive_area.state.append_bytes(&data, time);
+                if let Some(log) = &mut self.log {
+                    let result = log.write_rx(&data, time);
+                    self.check_log(result);
+                }
+                let replies = self.receive_area.terminal_replies();
+                self.update(replies);
+            }
+            SerialEvent::Sent { data, echo, time } => {
+                self.status_bar.add_tx(data.len());
+                if let Some(echo) = &echo {
+                    self.receive_area.state.append_sent(echo);
+                }
+                if let Some(log) = &mut self.log {
+                    let result = log.write_tx(&data, echo.as_deref(), time);
+                    self.check_log(result);
+                }
+            }
+            SerialEvent::WriteFailed(e) => self.update(Action::Error(format!("Write failed: {e}"))),
+            SerialEvent::ControlFailed(e) => {
+                self.update(Action::Error(format!("Failed to set control lines: {e}")))
+            }
+            SerialEvent::Break(duration) => {
+                let note = match duration {
+                    Some(d) => format!("BREAK {}ms", d.as_millis()),
+                    None => "BREAK 开始".to_string(),
+                };
+                self.receive_area.state.append_note(&note);
+            }
+            SerialEvent::Modem(lines) => self.modem = Some(lines),
+            SerialEvent::Disconnected(e) => {
+                self.port = None; // 关闭串口
+                self.modem = None;
+                if self.mode == Mode::Terminal {
+                    self.update(Action::SwitchMode(Mode::CommandInput));
+                }
+                if !self.auto_reconnect {
+                    self.receive_area
+                        .state
+                        .append_note(&format!("串口 {} 已断开: {e}", self.com));
+                    return self.update(Action::Error(format!(
+                        "Port {} disconnected: {e}",
+                        self.com
+                    )));
+                }
+                self.receive_area
+                    .state
+                    .append_note(&format!("串口 {} 已断开: {e}，等待设备重新连接", self.com));
+                self.update(Action::Notify(
+                    Level::Warning,
+                    format!("串口 {} 已断开，等待设备重新连接", self.com),
+                ));
+                self.reconnect = Some(Reconnect {
+                    path: self.com.clone(),
+                    serial: self.port_serial.clone(),
+                    next_attempt: Instant::now() + RECONNECT_INTERVAL,
+                });
+            }
+        }
+    }
 }
 
 fn app(terminal: &mut DefaultTerminal) -> Result<()> {
-    let mut app = App::new();
+    let (tx, rx) = mpsc::channel();
+    spawn_input_thread(tx.clone());
+    let ports = serialport::available_ports().unwrap_or_default();
+    spawn_port_thread(tx.clone(), ports.clone());
+    let mut app = App::new(tx, ports);
 
     loop {
-        app.try_read_serial_data().unwrap();
-
+        app.tick();
         terminal.draw(|frame| app.render(frame))?;
 
-        if event::poll(Duration::from_millis(100))?
-            && let Event::Key(key) = event::read()?
-            && key.kind == KeyEventKind::Press
-        {
-            // 全局快捷键处理 (比如 : 键)
-            if let KeyCode::Esc = key.code {
-                app.update(Action::SwitchMode(Mode::CommandInput));
-                continue;
+        // 阻塞等待下一个事件（超时也重绘一次），再在时间预算内处理积压的事件，
+        // 高速数据流下每帧合并处理多块数据，UI 不会被逐块重绘拖慢
+        let first = match rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let deadline = Instant::now() + FRAME_BUDGET;
+        let mut next = Some(first);
+        while let Some(event) = next {
+            app.handle_event(event)?;
+            if app.should_quit || Instant::now() >= deadline {
+                break;
             }
-
-            // 3. 将事件派发给当前活跃的组件
-            let action = app.get_active_component_mut().handle_key_events(key)?;
-
-            // 4. App 处理组件返回的 Action
-            app.update(action);
+            next = rx.try_recv().ok();
         }
 
         if app.should_quit {
@@ -0,0 +1,300 @@
+use std::time::Duration;
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+use serialport::SerialPort;
+
+// 单次等待 / 脉冲的上限
+const MAX_WAIT_MS: u64 = 10_000;
+// 一个序列的等待总时长和步数上限，执行期间之后的写请求要排队
+const MAX_SEQUENCE_MS: u64 = 10_000;
+const MAX_STEPS: usize = 64;
+// break 命令不带参数时的持续时间
+pub const DEFAULT_BREAK: Duration = Duration::from_millis(250);
+
+// 可以由程序控制的输出线
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlLine {
+    Dtr,
+    Rts,
+}
+
+impl ControlLine {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlLine::Dtr => "dtr",
+            ControlLine::Rts => "rts",
+        }
+    }
+}
+
+// 控制序列中的一步：设置某条线的电平、开始 / 结束 BREAK，或等待一段时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStep {
+    Set(ControlLine, bool),
+    Break(bool),
+    Wait(Duration),
+}
+
+impl LineStep {
+    // 写法与 reset def 的参数一致：dtr=on / rts=off / break=on / 100ms
+    pub fn describe(&self) -> String {
+        match self {
+            LineStep::Set(line, level) => format!("{}={}", line.name(), on_off(*level)),
+            LineStep::Break(on) => format!("break={}", on_off(*on)),
+            LineStep::Wait(d) => format!("{}ms", d.as_millis()),
+        }
+    }
+}
+
+// 命名的复位序列，如 esp32-boot
+#[derive(Debug, Clone)]
+pub struct ResetSequence {
+    pub name: String,
+    pub steps: Vec<LineStep>,
+}
+
+impl ResetSequence {
+    pub fn describe(&self) -> String {
+        let steps: Vec<String> = self.steps.iter().map(LineStep::describe).collect();
+        format!("{}: {}", self.name, steps.join(" "))
+    }
+}
+
+// 内置的复位序列，按 ESP32 开发板的自动下载电路：DTR 有效拉低 GPIO0，RTS 有效拉低 EN。
+// 其他接法的板子用 reset def 自己定义
+pub fn builtin_sequences() -> Vec<ResetSequence> {
+    let def = |name: &str, spec: &str| ResetSequence {
+        name: name.to_string(),
+        steps: parse_steps(&spec.split(' ').map(String::from).collect::<Vec<_>>())
+            .expect("built-in reset sequence"),
+    };
+    vec![
+        // 与 esptool 的 classic reset 相同：先拉低 EN 复位，放开 EN 时拉低 GPIO0，芯片启动进入下载模式
+        def(
+            "esp32-boot",
+            "dtr=off rts=on 100ms dtr=on rts=off 50ms dtr=off",
+        ),
+        def("esp32-reset", "dtr=off rts=on 100ms rts=off"),
+    ]
+}
+
+// dtr / rts 命令的参数：on | off | pulse <ms>（拉高 ms 毫秒后拉低）
+pub fn parse_line_command(line: ControlLine, args: &[String]) -> Result<Vec<LineStep>, String> {
+    let usage = || format!("Usage: {} on|off|pulse <ms>", line.name());
+    match args.first().map(|a| a.to_ascii_lowercase()).as_deref() {
+        Some("on") => Ok(vec![LineStep::Set(line, true)]),
+        Some("off") => Ok(vec![LineStep::Set(line, false)]),
+        Some("pulse") => {
+            let ms = args.get(1).ok_or_else(usage)?;
+            Ok(vec![
+                LineStep::Set(line, true),
+                LineStep::Wait(parse_wait(ms)?),
+                LineStep::Set(line, false),
+            ])
+        }
+        _ => Err(usage()),
+    }
+}
+
+// break 命令：发送持续 ms 毫秒的 BREAK
+pub fn parse_break_command(args: &[String]) -> Result<Duration, String> {
+    match args.first() {
+        Some(ms) => parse_wait(ms),
+        None => Ok(DEFAULT_BREAK),
+    }
+}
+
+// 解析 reset def 的步骤：dtr=on、rts=off、break=on、100 或 100ms（等待毫秒数）
+pub fn parse_steps(args: &[String]) -> Result<Vec<LineStep>, String> {
+    if args.is_empty() {
+        return Err("Empty reset sequence".to_string());
+    }
+    if args.len() > MAX_STEPS {
+        return Err(format!(
+            "Reset sequence too long: {} steps (max {MAX_STEPS})",
+            args.len()
+        ));
+    }
+    let steps = args
+        .iter()
+        .map(|arg| {
+            let Some((line, level)) = arg.split_once('=') else {
+                return parse_wait(arg).map(LineStep::Wait);
+            };
+            let level = match level.to_ascii_lowercase().as_str() {
+                "on" | "1" => true,
+                "off" | "0" => false,
+                _ => return Err(format!("Invalid level: {arg} (on/off)")),
+            };
+            match line.to_ascii_lowercase().as_str() {
+                "dtr" => Ok(LineStep::Set(ControlLine::Dtr, level)),
+                "rts" => Ok(LineStep::Set(ControlLine::Rts, level)),
+                "break" => Ok(LineStep::Break(level)),
+                _ => Err(format!("Unknown control line: {line} (dtr/rts/break)")),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let total: Duration = steps
+        .iter()
+        .map(|step| match step {
+            LineStep::Wait(d) => *d,
+            _ => Duration::ZERO,
+        })
+        .sum();
+    if total > Duration::from_millis(MAX_SEQUENCE_MS) {
+        return Err(format!(
+            "Reset sequence too long: {}ms of waits (max {MAX_SEQUENCE_MS} ms)",
+            total.as_millis()
+        ));
+    }
+    Ok(steps)
+}
+
+fn parse_wait(s: &str) -> Result<Duration, String> {
+    let digits = s.strip_suffix("ms").unwrap_or(s);
+    match digits.parse::<u64>() {
+        Ok(ms) if ms <= MAX_WAIT_MS => Ok(Duration::from_millis(ms)),
+        _ => Err(format!("Invalid delay: {s} (0-{MAX_WAIT_MS} ms)")),
+    }
+}
+
+fn on_off(level: bool) -> &'static str {
+    if level { "on" } else { "off" }
+}
+
+// 各控制线的状态，None 表示未知（输出线打开后未设置过，或驱动不支持读取）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ModemLines {
+    pub dtr: Option<bool>,
+    pub rts: Option<bool>,
+    pub cts: Option<bool>,
+    pub dsr: Option<bool>,
+    pub cd: Option<bool>,
+    pub ri: Option<bool>,
+}
+
+impl ModemLines {
+    // 读取输入线，输出线保持不变
+    pub fn poll(&mut self, port: &mut dyn SerialPort) {
+        self.cts = port.read_clear_to_send().ok();
+        self.dsr = port.read_data_set_ready().ok();
+        self.cd = port.read_carrier_detect().ok();
+        self.ri = port.read_ring_indicator().ok();
+    }
+
+    // 状态条：有效为绿色，无效为灰色，未知显示问号
+    pub fn strip(&self) -> Line<'static> {
+        let lines = [
+            ("DTR", self.dtr),
+            ("RTS", self.rts),
+            ("CTS", self.cts),
+            ("DSR", self.dsr),
+            ("CD", self.cd),
+            ("RI", self.ri),
+        ];
+        let mut spans = Vec::new();
+        for (name, level) in lines {
+            let (mark, color) = match level {
+                Some(true) => ("●", Color::LightGreen),
+                Some(false) => ("○", Color::DarkGray),
+                None => ("?", Color::Yellow),
+            };
+            spans.push(Span::styled(
+                format!("{name}{mark} "),
+                Style::new().fg(color),
+            ));
+        }
+        Line::from(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn line_commands() {
+        assert_eq!(
+            parse_line_command(ControlLine::Dtr, &args("ON")).unwrap(),
+            [LineStep::Set(ControlLine::Dtr, true)]
+        );
+        assert_eq!(
+            parse_line_command(ControlLine::Rts, &args("pulse 20ms")).unwrap(),
+            [
+                LineStep::Set(ControlLine::Rts, true),
+                LineStep::Wait(Duration::from_millis(20)),
+                LineStep::Set(ControlLine::Rts, false),
+            ]
+        );
+        for bad in ["", "high", "pulse", "pulse -1", "pulse 10001"] {
+            assert!(
+                parse_line_command(ControlLine::Dtr, &args(bad)).is_err(),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn break_command() {
+        assert_eq!(parse_break_command(&[]).unwrap(), DEFAULT_BREAK);
+        assert_eq!(
+            parse_break_command(&args("10000")).unwrap(),
+            Duration::from_secs(10)
+        );
+        assert_eq!(
+            parse_break_command(&args("10001")).unwrap_err(),
+            "Invalid delay: 10001 (0-10000 ms)"
+        );
+    }
+
+    #[test]
+    fn steps() {
+        let steps = parse_steps(&args("dtr=off RTS=1 100ms break=on 0 break=off")).unwrap();
+        assert_eq!(
+            steps,
+            [
+                LineStep::Set(ControlLine::Dtr, false),
+                LineStep::Set(ControlLine::Rts, true),
+                LineStep::Wait(Duration::from_millis(100)),
+                LineStep::Break(true),
+                LineStep::Wait(Duration::ZERO),
+                LineStep::Break(false),
+            ]
+        );
+        let described: Vec<String> = steps.iter().map(LineStep::describe).collect();
+        assert_eq!(
+            described.join(" "),
+            "dtr=off rts=on 100ms break=on 0ms break=off"
+        );
+    }
+
+    #[test]
+    fn invalid_steps() {
+        assert_eq!(parse_steps(&[]).unwrap_err(), "Empty reset sequence");
+        assert!(parse_steps(&args("dtr=maybe")).is_err());
+        assert!(parse_steps(&args("cts=on")).is_err());
+        assert!(parse_steps(&args("10s")).is_err());
+        assert!(parse_steps(&args("ms")).is_err());
+    }
+
+    #[test]
+    fn sequence_limits() {
+        assert!(parse_steps(&args("5000 5000")).is_ok());
+        assert!(parse_steps(&args("5000 5000 1")).is_err());
+        assert!(parse_steps(&vec!["dtr=on".to_string(); MAX_STEPS]).is_ok());
+        assert!(parse_steps(&vec!["dtr=on".to_string(); MAX_STEPS + 1]).is_err());
+    }
+
+    #[test]
+    fn builtin_sequences_parse() {
+        let names: Vec<String> = builtin_sequences().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["esp32-boot", "esp32-reset"]);
+    }
+}
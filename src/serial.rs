@@ -0,0 +1,338 @@
+use std::io;
+
+use serialport::{
+    DataBits, ErrorKind, FlowControl, Parity, SerialPort, SerialPortInfo, SerialPortType, StopBits,
+    UsbPortInfo,
+};
+
+// 列表中可选的取值，顺序即显示顺序
+pub const BAUD_RATES: [u32; 24] = [
+    300, 600, 1200, 2400, 4800, 9600, 14400, 19200, 38400, 57600, 115200, 230400, 460800, 500000,
+    576000, 921600, 1000000, 1152000, 1500000, 2000000, 2500000, 3000000, 3500000, 4000000,
+];
+pub const DATA_BITS_ITEMS: [&str; 4] = ["5", "6", "7", "8"];
+pub const PARITY_ITEMS: [&str; 3] = ["none", "odd", "even"];
+pub const STOP_BITS_ITEMS: [&str; 2] = ["1", "2"];
+pub const FLOW_CONTROL_ITEMS: [&str; 3] = ["none", "software", "hardware"];
+
+// 串口线路参数（波特率以外的部分），默认 8N1 无流控
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineSettings {
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+}
+
+impl Default for LineSettings {
+    fn default() -> Self {
+        Self {
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+        }
+    }
+}
+
+impl LineSettings {
+    // 形如 "8N1 none" 的简写
+    pub fn summary(&self) -> String {
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Odd => 'O',
+            Parity::Even => 'E',
+        };
+        let flow = match self.flow_control {
+            FlowControl::None => "none",
+            FlowControl::Software => "soft",
+            FlowControl::Hardware => "hard",
+        };
+        format!(
+            "{}{}{} {}",
+            u8::from(self.data_bits),
+            parity,
+            u8::from(self.stop_bits),
+            flow
+        )
+    }
+
+    // 应用到已经打开的串口上
+    pub fn apply(&self, port: &mut dyn SerialPort) -> serialport::Result<()> {
+        port.set_data_bits(self.data_bits)?;
+        port.set_parity(self.parity)?;
+        port.set_stop_bits(self.stop_bits)?;
+        port.set_flow_control(self.flow_control)
+    }
+}
+
+// 接受任意正整数波特率，是否真正支持由驱动在打开 / 设置时决定
+pub fn parse_baud_rate(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(rate) if rate > 0 => Ok(rate),
+        _ => Err(format!("Invalid baud rate: {s}")),
+    }
+}
+
+pub fn parse_data_bits(s: &str) -> Result<DataBits, String> {
+    match s {
+        "5" => Ok(DataBits::Five),
+        "6" => Ok(DataBits::Six),
+        "7" => Ok(DataBits::Seven),
+        "8" => Ok(DataBits::Eight),
+        _ => Err(format!("Invalid data bits: {s} (5/6/7/8)")),
+    }
+}
+
+pub fn parse_parity(s: &str) -> Result<Parity, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "none" | "n" => Ok(Parity::None),
+        "odd" | "o" => Ok(Parity::Odd),
+        "even" | "e" => Ok(Parity::Even),
+        _ => Err(format!("Invalid parity: {s} (none/odd/even)")),
+    }
+}
+
+pub fn parse_stop_bits(s: &str) -> Result<StopBits, String> {
+    match s {
+        "1" => Ok(StopBits::One),
+        "2" => Ok(StopBits::Two),
+        _ => Err(format!("Invalid stop bits: {s} (1/2)")),
+    }
+}
+
+pub fn parse_flow_control(s: &str) -> Result<FlowControl, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "none" => Ok(FlowControl::None),
+        "software" | "soft" | "xonxoff" => Ok(FlowControl::Software),
+        "hardware" | "hard" | "rtscts" => Ok(FlowControl::Hardware),
+        _ => Err(format!(
+            "Invalid flow control: {s} (none/software/hardware)"
+        )),
+    }
+}
+
+// 列表中显示的串口名称，USB 设备附带厂商、型号和序列号，
+// 如 "ttyUSB0 — FTDI FT232R [A50285BI]"
+pub fn port_label(info: &SerialPortInfo) -> String {
+    let name = short_name(&info.port_name);
+    let SerialPortType::UsbPort(usb) = &info.port_type else {
+        return name.to_string();
+    };
+    let mut label = format!("{name} — {}", usb_description(usb));
+    if let Some(serial) = &usb.serial_number {
+        label.push_str(&format!(" [{serial}]"));
+    }
+    label
+}
+
+// 详情面板中的各行
+pub fn port_details(info: &SerialPortInfo) -> Vec<String> {
+    let mut lines = vec![format!("路径 {}", info.port_name)];
+    match &info.port_type {
+        SerialPortType::UsbPort(usb) => {
+            lines.push("类型 USB".to_string());
+            lines.push(format!("VID:PID {:04x}:{:04x}", usb.vid, usb.pid));
+            let fields = [
+                ("序列号", &usb.serial_number),
+                ("厂商", &usb.manufacturer),
+                ("产品", &usb.product),
+            ];
+            for (name, value) in fields {
+                lines.push(format!("{name} {}", value.as_deref().unwrap_or("-")));
+            }
+        }
+        SerialPortType::PciPort => lines.push("类型 PCI".to_string()),
+        SerialPortType::BluetoothPort => lines.push("类型 蓝牙".to_string()),
+        SerialPortType::Unknown => lines.push("类型 未知".to_string()),
+    }
+    lines
+}
+
+// 按名称、VID:PID 或 USB 序列号查找串口，设备重新编号后脚本仍能找到同一个设备。
+// 不在列表中的路径（如 /dev/pts/3）原样接受，是否可用由打开时决定
+pub fn find_port(ports: &[SerialPortInfo], spec: &str) -> Result<String, String> {
+    let by_name: Vec<&SerialPortInfo> = ports
+        .iter()
+        .filter(|p| {
+            p.port_name == spec || short_name(&p.port_name) == spec || port_label(p) == spec
+        })
+        .collect();
+    let matches = if by_name.is_empty() {
+        let ids = parse_vid_pid(spec);
+        ports
+            .iter()
+            .filter(|p| {
+                let SerialPortType::UsbPort(usb) = &p.port_type else {
+                    return false;
+                };
+                ids == Some((usb.vid, usb.pid)) || usb.serial_number.as_deref() == Some(spec)
+            })
+            .collect()
+    } else {
+        by_name
+    };
+    match matches.as_slice() {
+        [port] => Ok(port.port_name.clone()),
+        [] if spec.starts_with('/') || spec.to_ascii_uppercase().starts_with("COM") => {
+            Ok(spec.to_string())
+        }
+        [] => Err(format!(
+            "No port matches {spec} (name, VID:PID or serial number)"
+        )),
+        _ => Err(format!(
+            "Multiple ports match {spec}: {}",
+            matches
+                .iter()
+                .map(|p| short_name(&p.port_name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+// 去掉 /dev/ 等目录前缀
+fn short_name(port_name: &str) -> &str {
+    port_name.rsplit('/').next().unwrap_or(port_name)
+}
+
+// 厂商 + 型号，型号里已经带厂商名时不重复；都没有时显示 VID:PID
+fn usb_description(usb: &UsbPortInfo) -> String {
+    match (&usb.manufacturer, &usb.product) {
+        (Some(m), Some(p)) if !p.starts_with(m.as_str()) => format!("{m} {p}"),
+        (_, Some(p)) => p.clone(),
+        (Some(m), None) => m.clone(),
+        (None, None) => format!("{:04x}:{:04x}", usb.vid, usb.pid),
+    }
+}
+
+// 形如 0403:6001 的十六进制 VID:PID
+fn parse_vid_pid(s: &str) -> Option<(u16, u16)> {
+    let (vid, pid) = s.split_once(':')?;
+    let parse = |v: &str| {
+        // from_str_radix 接受开头的 '+'，先确认都是十六进制数字
+        (v.len() == 4 && v.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| u16::from_str_radix(v, 16).ok())
+            .flatten()
+    };
+    Some((parse(vid)?, parse(pid)?))
+}
+
+// 打开失败时给出可操作的提示：被占用、没有权限、设备不存在
+pub fn open_error_message(port: &str, rate: u32, e: &serialport::Error) -> String {
+    let hint = match e.kind() {
+        // Linux 上 TIOCEXCL / flock 加锁失败都报告为 NoDevice
+        ErrorKind::NoDevice => {
+            "the port is busy, close the other program using it (minicom, screen, ModemManager ...)"
+        }
+        ErrorKind::Io(io::ErrorKind::PermissionDenied) => {
+            if cfg!(windows) {
+                "access denied, the port may be in use by another program"
+            } else {
+                "permission denied, add your user to the dialout (or uucp) group and log in again"
+            }
+        }
+        ErrorKind::Io(io::ErrorKind::NotFound) => {
+            "no such device, check the cable and run refresh to list ports"
+        }
+        ErrorKind::InvalidInput => "invalid settings, check the baud rate and line settings",
+        _ => return format!("Failed to open {port} at {rate} baud: {e}"),
+    };
+    format!("Failed to open {port}: {hint} ({e})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usb(name: &str, vid: u16, pid: u16, serial: Option<&str>) -> SerialPortInfo {
+        SerialPortInfo {
+            port_name: name.to_string(),
+            port_type: SerialPortType::UsbPort(UsbPortInfo {
+                vid,
+                pid,
+                serial_number: serial.map(String::from),
+                manufacturer: Some("FTDI".to_string()),
+                product: Some("FT232R".to_string()),
+            }),
+        }
+    }
+
+    fn ports() -> Vec<SerialPortInfo> {
+        vec![
+            usb("/dev/ttyUSB0", 0x0403, 0x6001, Some("A50285BI")),
+            usb("/dev/ttyUSB1", 0x0403, 0x6001, Some("B1234567")),
+            usb("/dev/ttyACM0", 0x303a, 0x1001, None),
+            SerialPortInfo {
+                port_name: "/dev/ttyS0".to_string(),
+                port_type: SerialPortType::Unknown,
+            },
+        ]
+    }
+
+    #[test]
+    fn vid_pid() {
+        assert_eq!(parse_vid_pid("0403:6001"), Some((0x0403, 0x6001)));
+        assert_eq!(parse_vid_pid("303A:1001"), Some((0x303a, 0x1001)));
+        assert_eq!(parse_vid_pid("403:6001"), None);
+        assert_eq!(parse_vid_pid("0403:60011"), None);
+        assert_eq!(parse_vid_pid("0403-6001"), None);
+        assert_eq!(parse_vid_pid("04g3:6001"), None);
+        assert_eq!(parse_vid_pid("+403:6001"), None);
+    }
+
+    #[test]
+    fn find_port_by_name() {
+        let ports = ports();
+        assert_eq!(find_port(&ports, "/dev/ttyUSB1").unwrap(), "/dev/ttyUSB1");
+        assert_eq!(find_port(&ports, "ttyS0").unwrap(), "/dev/ttyS0");
+        assert_eq!(
+            find_port(&ports, "ttyUSB0 — FTDI FT232R [A50285BI]").unwrap(),
+            "/dev/ttyUSB0"
+        );
+    }
+
+    #[test]
+    fn find_port_by_usb_ids() {
+        let ports = ports();
+        assert_eq!(find_port(&ports, "303a:1001").unwrap(), "/dev/ttyACM0");
+        assert_eq!(find_port(&ports, "B1234567").unwrap(), "/dev/ttyUSB1");
+        assert_eq!(
+            find_port(&ports, "0403:6001").unwrap_err(),
+            "Multiple ports match 0403:6001: ttyUSB0, ttyUSB1"
+        );
+    }
+
+    #[test]
+    fn find_port_unknown() {
+        let ports = ports();
+        assert_eq!(find_port(&ports, "/dev/pts/3").unwrap(), "/dev/pts/3");
+        assert_eq!(find_port(&ports, "com7").unwrap(), "com7");
+        assert!(find_port(&ports, "1a86:7523").is_err());
+        assert!(find_port(&[], "ttyUSB0").is_err());
+    }
+
+    #[test]
+    fn line_settings() {
+        assert_eq!(parse_data_bits("7").unwrap(), DataBits::Seven);
+        assert!(parse_data_bits("9").is_err());
+        assert_eq!(parse_parity("EVEN").unwrap(), Parity::Even);
+        assert_eq!(parse_parity("o").unwrap(), Parity::Odd);
+        assert!(parse_parity("mark").is_err());
+        assert_eq!(parse_stop_bits("2").unwrap(), StopBits::Two);
+        assert!(parse_stop_bits("1.5").is_err());
+        assert_eq!(parse_flow_control("rtscts").unwrap(), FlowControl::Hardware);
+        assert!(parse_flow_control("xon").is_err());
+    }
+
+    #[test]
+    fn baud_rates() {
+        assert_eq!(parse_baud_rate("115200").unwrap(), 115200);
+        assert_eq!(parse_baud_rate("74880").unwrap(), 74880);
+        assert_eq!(parse_baud_rate("4000000").unwrap(), 4_000_000);
+        assert!(parse_baud_rate("0").is_err());
+        assert!(parse_baud_rate("-9600").is_err());
+        assert!(parse_baud_rate("fast").is_err());
+        assert!(parse_baud_rate("4294967296").is_err());
+    }
+}
@@ -0,0 +1,314 @@
+use crate::encoding::Charset;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+// 文本视图中 ANSI 转义序列的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnsiMode {
+    #[default]
+    Render, // 解析 SGR 颜色 / 字体，其余序列（光标移动、清屏等）去掉
+    Strip,   // 去掉所有转义序列，显示纯文本
+    Literal, // 原样显示转义序列（ESC 显示为 ^[），用于调试
+}
+
+impl AnsiMode {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "on" => Ok(AnsiMode::Render),
+            "off" => Ok(AnsiMode::Strip),
+            "raw" => Ok(AnsiMode::Literal),
+            _ => Err(format!("Unknown ansi mode: {s} (on/off/raw)")),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AnsiMode::Render => "ANSI",
+            AnsiMode::Strip => "NOANSI",
+            AnsiMode::Literal => "ESC",
+        }
+    }
+}
+
+const ESC: u8 = 0x1B;
+const BEL: u8 = 0x07;
+
+// 字节流中的一段：普通文本、SGR 序列（只保留参数部分）、其他转义序列
+enum Segment<'a> {
+    Text(&'a [u8]),
+    Sgr(&'a [u8]),
+    Other(&'a [u8]),
+}
+
+// 切分出 ESC 开头的一个完整序列，返回它的长度；不完整的序列取到末尾
+fn escape_len(bytes: &[u8]) -> usize {
+    let find_from = |start: usize, pred: &dyn Fn(u8) -> bool| {
+        bytes[start.min(bytes.len())..]
+            .iter()
+            .position(|&b| pred(b))
+            .map_or(bytes.len(), |i| start + i + 1)
+    };
+    match bytes.get(1) {
+        None => 1,
+        // CSI：ESC [ 参数 中间字节 结束字节(0x40-0x7E)
+        Some(b'[') => find_from(2, &|b| (0x40..=0x7E).contains(&b)),
+        // OSC / DCS 等字符串序列：以 BEL 或 ESC \ 结束
+        Some(b']' | b'P' | b'X' | b'^' | b'_') => {
+            let mut i = 2;
+            while i < bytes.len() {
+                match bytes[i] {
+                    BEL => return i + 1,
+                    ESC if bytes.get(i + 1) == Some(&b'\\') => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            bytes.len()
+        }
+        // ESC 中间字节 结束字节，如 ESC ( B
+        Some(0x20..=0x2F) => find_from(2, &|b| !(0x20..=0x2F).contains(&b)),
+        Some(_) => 2,
+    }
+}
+
+fn segments(bytes: &[u8]) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let Some(start) = rest.iter().position(|&b| b == ESC) else {
+            segments.push(Segment::Text(rest));
+            break;
+        };
+        if start > 0 {
+            segments.push(Segment::Text(&rest[..start]));
+        }
+        let rest_esc = &rest[start..];
+        let len = escape_len(rest_esc);
+        let seq = &rest_esc[..len];
+        match seq
+            .strip_prefix(b"\x1b[")
+            .and_then(|s| s.strip_suffix(b"m"))
+        {
+            Some(params)
+                if params
+                    .iter()
+                    .all(|&b| b.is_ascii_digit() || b == b';' || b == b':') =>
+            {
+                segments.push(Segment::Sgr(params))
+            }
+            _ => segments.push(Segment::Other(seq)),
+        }
+        rest = &rest_esc[len..];
+    }
+    segments
+}
+
+// 按 SGR 参数修改样式
+pub fn apply_sgr(mut style: Style, params: &[u8]) -> Style {
+    let params = String::from_utf8_lossy(params);
+    // 空参数（ESC[m）等同于 0
+    let mut codes = params
+        .split([';', ':'])
+        .map(|p| p.parse::<u16>().unwrap_or(0));
+    while let Some(code) = codes.next() {
+        style = match code {
+            0 => Style::new(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            6 => style.add_modifier(Modifier::RAPID_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            8 => style.add_modifier(Modifier::HIDDEN),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            21 | 22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            28 => style.remove_modifier(Modifier::HIDDEN),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(Color::Indexed((code - 30) as u8)),
+            38 => match extended_color(&mut codes) {
+                Some(color) => style.fg(color),
+                None => style,
+            },
+            39 => Style { fg: None, ..style },
+            40..=47 => style.bg(Color::Indexed((code - 40) as u8)),
+            48 => match extended_color(&mut codes) {
+                Some(color) => style.bg(color),
+                None => style,
+            },
+            49 => Style { bg: None, ..style },
+            90..=97 => style.fg(Color::Indexed((code - 90 + 8) as u8)),
+            100..=107 => style.bg(Color::Indexed((code - 100 + 8) as u8)),
+            _ => style,
+        };
+    }
+    style
+}
+
+// 38 / 48 之后的 5;n（256 色）或 2;r;g;b（真彩色）
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    match codes.next()? {
+        5 => Some(Color::Indexed(codes.next()? as u8)),
+        2 => {
+            let r = codes.next()? as u8;
+            let g = codes.next()? as u8;
+            let b = codes.next()? as u8;
+            Some(Color::Rgb(r, g, b))
+        }
+        _ => None,
+    }
+}
+
+// 从 style 开始解析 bytes，得到结束时的样式（下一行的初始样式）
+pub fn ansi_end_style(bytes: &[u8], mut style: Style) -> Style {
+    for segment in segments(bytes) {
+        if let Segment::Sgr(params) = segment {
+            style = apply_sgr(style, params);
+        }
+    }
+    style
+}
+
+// 去掉所有转义序列，用于搜索、过滤等按内容匹配的场合
+pub fn strip_ansi(bytes: &[u8]) -> Vec<u8> {
+    if !bytes.contains(&ESC) {
+        return bytes.to_vec();
+    }
+    segments(bytes)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Text(text) => Some(text),
+            _ => None,
+        })
+        .flatten()
+        .copied()
+        .collect()
+}
+
+// 按 mode 把一行数据转换为屏幕行，style 为行首的 SGR 样式
+pub fn ansi_line(bytes: &[u8], style: Style, mode: AnsiMode, charset: Charset) -> Line<'static> {
+    // ESC 不会出现在多字节字符中间，按转义序列切开后再逐段解码
+    let text = |b: &[u8]| charset.decode(b).into_owned();
+    match mode {
+        AnsiMode::Strip => Line::raw(text(&strip_ansi(bytes))),
+        AnsiMode::Render => {
+            let mut style = style;
+            let mut line = Line::default();
+            for segment in segments(bytes) {
+                match segment {
+                    Segment::Text(t) => line.push_span(Span::styled(text(t), style)),
+                    Segment::Sgr(params) => style = apply_sgr(style, params),
+                    Segment::Other(_) => {}
+                }
+            }
+            line
+        }
+        AnsiMode::Literal => {
+            let escape_style = Style::new().fg(Color::Yellow);
+            let mut line = Line::default();
+            for segment in segments(bytes) {
+                let seq = match segment {
+                    Segment::Text(t) => {
+                        line.push_span(Span::raw(text(t)));
+                        continue;
+                    }
+                    Segment::Sgr(params) => format!("^[[{}m", text(params)),
+                    Segment::Other(seq) => caret(&text(seq)),
+                };
+                line.push_span(Span::styled(seq, escape_style));
+            }
+            line
+        }
+    }
+}
+
+// 控制字符显示为 ^ 记法，如 ESC -> ^[，BEL -> ^G
+fn caret(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() * 2);
+    for c in s.chars() {
+        if c < ' ' {
+            out.push('^');
+            out.push((c as u8 + 0x40) as char);
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_lengths() {
+        assert_eq!(escape_len(b"\x1b[1;31mabc"), 7);
+        assert_eq!(escape_len(b"\x1b[2K"), 4);
+        assert_eq!(escape_len(b"\x1b]0;title\x07x"), 10);
+        assert_eq!(escape_len(b"\x1b]0;t\x1b\\x"), 7);
+        assert_eq!(escape_len(b"\x1b(Bx"), 3);
+        assert_eq!(escape_len(b"\x1b7x"), 2);
+        // 不完整的序列取到末尾
+        assert_eq!(escape_len(b"\x1b"), 1);
+        assert_eq!(escape_len(b"\x1b[12"), 4);
+        assert_eq!(escape_len(b"\x1b]0;ti"), 6);
+    }
+
+    #[test]
+    fn sgr() {
+        let red = apply_sgr(Style::new(), b"1;31");
+        assert_eq!(red, Style::new().fg(Color::Indexed(1)).bold());
+        assert_eq!(apply_sgr(red, b""), Style::new());
+        let plain = apply_sgr(red, b"22;39");
+        assert_eq!((plain.fg, plain.add_modifier), (None, Modifier::empty()));
+        assert_eq!(
+            apply_sgr(Style::new(), b"38;5;208;48;2;1;2;3"),
+            Style::new().fg(Color::Indexed(208)).bg(Color::Rgb(1, 2, 3))
+        );
+        assert_eq!(
+            apply_sgr(Style::new(), b"38:5:9"),
+            Style::new().fg(Color::Indexed(9))
+        );
+        assert_eq!(
+            apply_sgr(Style::new(), b"97"),
+            Style::new().fg(Color::Indexed(15))
+        );
+        // 不完整或超出范围的参数不改变样式
+        assert_eq!(apply_sgr(Style::new(), b"38;2;1"), Style::new());
+        assert_eq!(apply_sgr(Style::new(), b"99999"), Style::new());
+    }
+
+    #[test]
+    fn strip() {
+        assert_eq!(strip_ansi(b"plain"), b"plain");
+        assert_eq!(
+            strip_ansi(b"\x1b[32mok\x1b[0m \x1b[2Kdone\x1b]0;t\x07"),
+            b"ok done"
+        );
+        assert_eq!(strip_ansi(b"tail\x1b[3"), b"tail");
+    }
+
+    #[test]
+    fn end_style() {
+        let style = ansi_end_style(b"\x1b[31mred\x1b[4m", Style::new());
+        assert_eq!(style, Style::new().fg(Color::Indexed(1)).underlined());
+    }
+
+    #[test]
+    fn render_modes() {
+        let bytes = b"\x1b[31mhi\x1b[0m\x1b[K";
+        let charset = Charset::default();
+        let render = ansi_line(bytes, Style::new(), AnsiMode::Render, charset);
+        assert_eq!(render.to_string(), "hi");
+        assert_eq!(render.spans[0].style, Style::new().fg(Color::Indexed(1)));
+        let strip = ansi_line(bytes, Style::new(), AnsiMode::Strip, charset);
+        assert_eq!(strip.to_string(), "hi");
+        let literal = ansi_line(bytes, Style::new(), AnsiMode::Literal, charset);
+        assert_eq!(literal.to_string(), "^[[31mhi^[[0m^[[K");
+    }
+}
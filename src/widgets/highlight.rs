@@ -0,0 +1,72 @@
+use ratatui::style::{Color, Modifier, Style};
+use regex::Regex;
+
+// 一条高亮规则：正则匹配到的接收行整行着色，或只给匹配到的文字着色
+#[derive(Debug, Clone)]
+pub struct HighlightRule {
+    pub pattern: Regex,
+    pub style: Style,
+    pub whole_line: bool,
+    spec: String, // 样式部分的原文，列出规则时显示
+}
+
+impl HighlightRule {
+    // 解析 hl add 参数：<regex> [color] [bg=color] [bold|dim|italic|underline|reversed] [-m]
+    // color 可以是 red / lightred / #ff8800 / 0-255，-m 表示只高亮匹配部分
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let usage = || {
+            "Usage: hl add <regex> [color] [bg=color] [bold|dim|italic|underline|reversed] [-m]"
+                .to_string()
+        };
+        let (pattern, rest) = args.split_first().ok_or_else(usage)?;
+        let pattern =
+            Regex::new(pattern).map_err(|e| format!("Invalid regex \"{pattern}\": {e}"))?;
+        let mut style = Style::new();
+        let mut whole_line = true;
+        let mut spec = Vec::new();
+        for arg in rest {
+            let lower = arg.to_ascii_lowercase();
+            match lower.as_str() {
+                "-m" => whole_line = false,
+                "bold" => style = style.add_modifier(Modifier::BOLD),
+                "dim" => style = style.add_modifier(Modifier::DIM),
+                "italic" => style = style.add_modifier(Modifier::ITALIC),
+                "underline" => style = style.add_modifier(Modifier::UNDERLINED),
+                "reversed" => style = style.add_modifier(Modifier::REVERSED),
+                _ => match lower.strip_prefix("bg=") {
+                    Some(color) => style = style.bg(parse_color(color)?),
+                    None => {
+                        style = style.fg(parse_color(lower.strip_prefix("fg=").unwrap_or(&lower))?)
+                    }
+                },
+            }
+            if arg != "-m" {
+                spec.push(lower);
+            }
+        }
+        if spec.is_empty() {
+            return Err(usage());
+        }
+        Ok(Self {
+            pattern,
+            style,
+            whole_line,
+            spec: spec.join(" "),
+        })
+    }
+
+    // 如 "ERROR → red bold (整行)"
+    pub fn describe(&self) -> String {
+        let scope = if self.whole_line {
+            "整行"
+        } else {
+            "匹配部分"
+        };
+        format!("{} → {} ({scope})", self.pattern.as_str(), self.spec)
+    }
+}
+
+fn parse_color(s: &str) -> Result<Color, String> {
+    s.parse::<Color>()
+        .map_err(|_| format!("Unknown color: {s}"))
+}
@@ -4,3 +4,9 @@ mod text_input;
 pub use text_input::*;
 mod receive_text;
 pub use receive_text::*;
+mod highlight;
+mod search;
+pub use highlight::*;
+mod ansi;
+pub use ansi::AnsiMode;
+mod vt100;
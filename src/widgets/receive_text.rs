@@ -1,43 +1,1372 @@
+use std::{borrow::Cow, collections::VecDeque};
+
+use chrono::{DateTime, Local};
 use ratatui::widgets::Widget;
 use ratatui::{prelude::*, style::Color, widgets::*};
-#[derive(Default)]
+
+use super::{
+    HighlightRule,
+    ansi::{AnsiMode, ansi_end_style, ansi_line, strip_ansi},
+    search::{Pattern, highlight, line_text},
+    vt100::Screen,
+};
+use crate::encoding::Charset;
+use encoding_rs::DecoderResult;
+
+// 数据方向：接收到的数据 / 本端发送的回显 / 程序自身的提示信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Rx,
+    Tx,
+    Note,
+}
+
+impl Direction {
+    fn style(&self) -> Style {
+        match self {
+            Direction::Rx => Style::new(),
+            Direction::Tx => Style::new().fg(Color::LightGreen),
+            Direction::Note => Style::new().fg(Color::Cyan).add_modifier(Modifier::ITALIC),
+        }
+    }
+}
+
+// 接收区显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewMode {
+    #[default]
+    Text, // 按 UTF-8 解码显示
+    Hex,   // 类似 xxd 的 偏移 / 十六进制 / ASCII 三栏
+    Mixed, // 可打印字符原样显示，其余显示为 <0x1B>
+}
+
+impl ViewMode {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(ViewMode::Text),
+            "hex" => Ok(ViewMode::Hex),
+            "mixed" => Ok(ViewMode::Mixed),
+            _ => Err(format!("Unknown view mode: {s} (text/hex/mixed)")),
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ViewMode::Text => "TEXT",
+            ViewMode::Hex => "HEX",
+            ViewMode::Mixed => "MIXED",
+        }
+    }
+}
+
+// 行首时间戳显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampMode {
+    #[default]
+    Off,
+    Absolute, // HH:MM:SS.mmm
+    Delta,    // 距上一行的时间差
+}
+
+impl TimestampMode {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(TimestampMode::Off),
+            "abs" | "absolute" => Ok(TimestampMode::Absolute),
+            "delta" => Ok(TimestampMode::Delta),
+            _ => Err(format!("Unknown timestamp mode: {s} (off/abs/delta)")),
+        }
+    }
+
+    // 依次切换 off -> abs -> delta -> off
+    pub fn next(&self) -> Self {
+        match self {
+            TimestampMode::Off => TimestampMode::Absolute,
+            TimestampMode::Absolute => TimestampMode::Delta,
+            TimestampMode::Delta => TimestampMode::Off,
+        }
+    }
+}
+
+// 接收数据的断行方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NewlineMode {
+    #[default]
+    Auto, // CR、LF、CRLF、LFCR 都算一次换行
+    Cr,   // 只在 CR 处断行，LF 不显示
+    Lf,   // 只在 LF 处断行，CR 不显示
+    CrLf, // 只在 CRLF 处断行，单独的 CR / LF 显示为控制符号
+    Show, // 在 LF 处断行，所有控制字符（包括换行符本身）显示为控制符号
+}
+
+impl NewlineMode {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(NewlineMode::Auto),
+            "cr" => Ok(NewlineMode::Cr),
+            "lf" => Ok(NewlineMode::Lf),
+            "crlf" => Ok(NewlineMode::CrLf),
+            "show" => Ok(NewlineMode::Show),
+            _ => Err(format!("Unknown newline mode: {s} (auto/cr/lf/crlf/show)")),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NewlineMode::Auto => "auto",
+            NewlineMode::Cr => "cr",
+            NewlineMode::Lf => "lf",
+            NewlineMode::CrLf => "crlf",
+            NewlineMode::Show => "show",
+        }
+    }
+
+    // 在 data 中找本行的结束位置，prev 为本行已有的内容。
+    // 返回 (本行取走的字节数, 换行符长度)，没有换行时返回 None
+    fn find_break(&self, prev: &[u8], data: &[u8]) -> Option<(usize, usize)> {
+        let find = |pred: &dyn Fn(u8) -> bool| data.iter().position(|&b| pred(b));
+        match self {
+            NewlineMode::Lf | NewlineMode::Show => find(&|b| b == b'\n').map(|i| (i + 1, 1)),
+            NewlineMode::Cr => find(&|b| b == b'\r').map(|i| (i + 1, 1)),
+            // 上一块以 CR 结尾、这一块以 LF 开头
+            NewlineMode::CrLf if prev.ends_with(b"\r") && data.first() == Some(&b'\n') => {
+                Some((1, 2))
+            }
+            NewlineMode::CrLf => data
+                .windows(2)
+                .position(|w| w == b"\r\n")
+                .map(|i| (i + 2, 2)),
+            NewlineMode::Auto => {
+                find(&|b| b == b'\r' || b == b'\n').map(|i| match data.get(i + 1) {
+                    Some(&next) if next == pair_of(data[i]) => (i + 2, 2),
+                    _ => (i + 1, 1),
+                })
+            }
+        }
+    }
+
+    // 文本视图中行内容的显示：去掉或替换行内残留的 CR / LF 等控制字符
+    fn display<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let map = |pred: &dyn Fn(char) -> bool, remove: bool| -> Cow<'a, str> {
+            if !text.chars().any(pred) {
+                return Cow::Borrowed(text);
+            }
+            text.chars()
+                .filter_map(|c| match pred(c) {
+                    true if remove => None,
+                    true => Some(control_symbol(c)),
+                    false => Some(c),
+                })
+                .collect()
+        };
+        match self {
+            NewlineMode::Auto => Cow::Borrowed(text),
+            NewlineMode::Cr => map(&|c| c == '\n', true),
+            NewlineMode::Lf => map(&|c| c == '\r', true),
+            NewlineMode::CrLf => map(&|c| c == '\r' || c == '\n', false),
+            // ESC 交给 ANSI 处理方式决定
+            NewlineMode::Show => map(&|c| c.is_ascii_control() && c != '\x1b', false),
+        }
+    }
+}
+
+// CR 与 LF 互为一对
+fn pair_of(b: u8) -> u8 {
+    if b == b'\r' { b'\n' } else { b'\r' }
+}
+
+// 控制字符对应的 Unicode 控制符号，如 CR -> ␍，LF -> ␊
+fn control_symbol(c: char) -> char {
+    match c {
+        '\x7f' => '\u{2421}',
+        _ => char::from_u32(0x2400 + c as u32).unwrap_or(c),
+    }
+}
+
+pub const HEX_ROW_BYTES: usize = 16;
+// 没有换行符的数据（如二进制流）超过这个长度也强制断行，保证每行大小有界
+const MAX_LINE_BYTES: usize = 4096;
+// 默认最多保留的行数
+pub const DEFAULT_SCROLLBACK: usize = 10_000;
+
+// 接收区中的一行：接收数据按 '\n' 断行（bytes 中保留换行符），每条发送回显各占一行
+struct RxLine {
+    dir: Direction,
+    bytes: Vec<u8>,
+    chunks: Vec<(usize, DateTime<Local>)>, // 行内各读取块的起始偏移和到达时间
+    offset: usize,                         // 行首在接收流中的全局偏移（hex 视图用）
+    complete: bool,                        // 已断行，后续数据进入新行
+    eol: usize,                            // 行尾换行符的字节数
+    style: Style,                          // 行首的 ANSI SGR 样式（沿用上一行结束时的样式）
+}
+
+impl RxLine {
+    fn new(dir: Direction, offset: usize, time: DateTime<Local>) -> Self {
+        Self {
+            dir,
+            bytes: Vec::new(),
+            chunks: vec![(0, time)],
+            offset,
+            complete: false,
+            eol: 0,
+            style: Style::new(),
+        }
+    }
+
+    // 行首的到达时间
+    fn time(&self) -> DateTime<Local> {
+        self.chunks[0].1
+    }
+
+    // offset 处的字节所在读取块的到达时间
+    fn time_at(&self, offset: usize) -> DateTime<Local> {
+        let i = self.chunks.partition_point(|(start, _)| *start <= offset);
+        self.chunks[i.saturating_sub(1)].1
+    }
+
+    // 去掉行尾换行符后的内容
+    fn content(&self) -> &[u8] {
+        &self.bytes[..self.bytes.len() - self.eol]
+    }
+
+    // 搜索、过滤用的文本（去掉 ANSI 转义序列后解码）。回显和提示信息本身就是 UTF-8
+    fn text(&self, charset: Charset) -> String {
+        let content = strip_ansi(self.content());
+        let text = match self.dir {
+            Direction::Rx => charset.decode(&content).into_owned(),
+            _ => String::from_utf8_lossy(&content).into_owned(),
+        };
+        // 按换行方式残留在行内的 CR / LF 不参与匹配
+        if text.contains(['\r', '\n']) {
+            text.replace(['\r', '\n'], "")
+        } else {
+            text
+        }
+    }
+}
+
+// 当前搜索：匹配的行（全局行号，升序）和当前所在的匹配行
+struct Search {
+    pattern: Pattern,
+    matches: Vec<u64>,
+    current: Option<u64>,
+}
+
+// 行过滤：只显示匹配（invert 时为不匹配）的行，提示信息总是显示
+struct Filter {
+    pattern: Pattern,
+    invert: bool,
+    lines: Vec<u64>, // 通过过滤的行（全局行号，升序）
+}
+
+impl Filter {
+    fn accepts(&self, line: &RxLine, charset: Charset) -> bool {
+        line.dir == Direction::Note || self.pattern.is_match(&line.text(charset)) != self.invert
+    }
+}
+
+// 接收区内容：按行保存的环形缓冲区，超过 max_lines 时丢弃最旧的行
 pub struct ReceiveTextState {
-    content: String,
-    scroll: u16,
+    lines: VecDeque<RxLine>,
+    max_lines: usize,
+    dropped: u64,    // 累计丢弃的行数
+    rx_total: usize, // 累计接收的字节数
+    view: ViewMode,
+    newline: NewlineMode,
+    pending_pair: Option<u8>, // auto 模式下等待并入上一行的 CR / LF
+    charset: Charset,         // 接收数据的字符集
+    ansi: AnsiMode,
+    timestamp: TimestampMode,
+    status: Option<String>, // 显示在标题栏右侧的状态，如日志文件
+    // 滚动位置：显示在最底部的行的全局行号（dropped + 下标），None 表示跟随最新数据
+    bottom: Option<u64>,
+    new_data: bool, // 暂停跟随期间是否有新数据到达
+    page: usize,    // 上次渲染时的可见行数，用于翻页
+    search: Option<Search>,
+    search_regex: bool, // 搜索按正则匹配
+    rules: Vec<HighlightRule>,
+    filter: Option<Filter>,
+    // 终端模式：接收区显示为 VT100 屏幕。屏幕在第一次进入终端模式时创建，之后一直接收数据
+    screen: Option<Screen>,
+    terminal: bool,
+    is_focus: bool,
 }
+
+impl Default for ReceiveTextState {
+    fn default() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            max_lines: DEFAULT_SCROLLBACK,
+            dropped: 0,
+            rx_total: 0,
+            view: ViewMode::default(),
+            newline: NewlineMode::default(),
+            pending_pair: None,
+            charset: Charset::default(),
+            ansi: AnsiMode::default(),
+            timestamp: TimestampMode::default(),
+            status: None,
+            bottom: None,
+            new_data: false,
+            page: 1,
+            search: None,
+            search_regex: false,
+            rules: Vec::new(),
+            filter: None,
+            screen: None,
+            terminal: false,
+            is_focus: false,
+        }
+    }
+}
+
 impl ReceiveTextState {
-    pub fn append_text(&mut self, str: &str) {
-        self.content += str;
+    pub fn append_bytes(&mut self, mut data: &[u8], time: DateTime<Local>) {
+        if let Some(screen) = &mut self.screen {
+            screen.feed(data);
+        }
+        // 未结束的接收行会被追加内容，需要从它开始重新搜索
+        let first = match self.lines.back() {
+            Some(line) if line.dir == Direction::Rx && !line.complete => self.last_line(),
+            _ => self.dropped + self.lines.len() as u64,
+        };
+        let newline = self.newline;
+        while !data.is_empty() {
+            // 上一行以 CR（或 LF）结束、接下来是配对的 LF（或 CR）时，并入上一行的换行符
+            if let Some(pair) = self.pending_pair.take()
+                && data[0] == pair
+                && let Some(line) = self.lines.back_mut()
+                && line.dir == Direction::Rx
+            {
+                line.bytes.push(pair);
+                line.eol += 1;
+                self.rx_total += 1;
+                data = &data[1..];
+                continue;
+            }
+            let line = match self.lines.back_mut() {
+                Some(line) if line.dir == Direction::Rx && !line.complete => {
+                    line.chunks.push((line.bytes.len(), time));
+                    line
+                }
+                _ => {
+                    let mut line = RxLine::new(Direction::Rx, self.rx_total, time);
+                    if let Some(prev) = self.lines.iter().rev().find(|l| l.dir == Direction::Rx) {
+                        line.style = ansi_end_style(&prev.bytes, prev.style);
+                    }
+                    self.lines.push_back(line);
+                    self.lines.back_mut().unwrap()
+                }
+            };
+            let room = (MAX_LINE_BYTES - line.bytes.len()).min(data.len());
+            let (take, eol) = newline
+                .find_break(&line.bytes, &data[..room])
+                .unwrap_or((room, 0));
+            line.bytes.extend_from_slice(&data[..take]);
+            line.eol = eol;
+            line.complete = eol > 0 || line.bytes.len() >= MAX_LINE_BYTES;
+            // auto 模式下单个换行符正好在这一块末尾（或行长上限处），配对的另一半可能在下一块开头
+            if newline == NewlineMode::Auto && eol == 1 && take == room {
+                self.pending_pair = Some(pair_of(data[take - 1]));
+            }
+            self.rx_total += take;
+            data = &data[take..];
+        }
+        self.new_data |= self.bottom.is_some();
+        self.rescan(first);
+        self.evict();
+    }
+
+    // 发送回显，单独占一行
+    pub fn append_sent(&mut self, str: &str) {
+        self.append_line(Direction::Tx, str);
+    }
+
+    // 提示信息（如命令输出），单独占一行，不计入接收数据
+    pub fn append_note(&mut self, str: &str) {
+        self.append_line(Direction::Note, str);
+    }
+
+    fn append_line(&mut self, dir: Direction, str: &str) {
+        self.pending_pair = None;
+        // 未结束的接收行就此断开，之后的接收数据另起一行
+        if let Some(line) = self.lines.back_mut() {
+            line.complete = true;
+        }
+        let mut line = RxLine::new(dir, self.rx_total, Local::now());
+        line.bytes = str.as_bytes().to_vec();
+        line.complete = true;
+        self.lines.push_back(line);
+        self.new_data |= self.bottom.is_some();
+        self.rescan(self.last_line());
+        self.evict();
+    }
+
+    pub fn set_max_lines(&mut self, max_lines: usize) {
+        self.max_lines = max_lines.max(1);
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+            self.dropped += 1;
+        }
+        let dropped = self.dropped;
+        if let Some(filter) = &mut self.filter {
+            filter.lines.retain(|&l| l >= dropped);
+        }
+        if let Some(search) = &mut self.search {
+            search.matches.retain(|&m| m >= dropped);
+            if search.current.is_some_and(|c| c < dropped) {
+                search.current = search.matches.first().copied();
+            }
+        }
+    }
+
+    // 从全局行号 first 开始重新计算过滤结果和搜索匹配（只搜索通过过滤的行）
+    fn rescan(&mut self, first: u64) {
+        let start = first.saturating_sub(self.dropped) as usize;
+        let dropped = self.dropped;
+        if let Some(filter) = &mut self.filter {
+            filter.lines.retain(|&l| l < first);
+            for (i, line) in self.lines.iter().enumerate().skip(start) {
+                if filter.accepts(line, self.charset) {
+                    filter.lines.push(dropped + i as u64);
+                }
+            }
+        }
+        let filter = self.filter.as_ref();
+        if let Some(search) = &mut self.search {
+            search.matches.retain(|&m| m < first);
+            for (i, line) in self.lines.iter().enumerate().skip(start) {
+                if filter.is_none_or(|f| f.accepts(line, self.charset))
+                    && search.pattern.is_match(&line.text(self.charset))
+                {
+                    search.matches.push(dropped + i as u64);
+                }
+            }
+        }
+    }
+
+    // 设置搜索内容并跳到离当前位置最近的（不晚于底部行的）匹配，空字符串清除搜索
+    pub fn set_search(&mut self, query: &str) -> Result<(), String> {
+        if query.is_empty() {
+            self.search = None;
+            return Ok(());
+        }
+        let pattern = Pattern::new(query, self.search_regex)?;
+        self.search = Some(Search {
+            pattern,
+            matches: Vec::new(),
+            current: None,
+        });
+        self.rescan(self.dropped);
+        let bottom = self.bottom.unwrap_or(self.last_line());
+        let search = self.search.as_mut().unwrap();
+        let i = search.matches.partition_point(|&m| m <= bottom);
+        search.current = i
+            .checked_sub(1)
+            .or((!search.matches.is_empty()).then_some(0))
+            .map(|i| search.matches[i]);
+        if let Some(line) = search.current {
+            self.scroll_to_line(line);
+        }
+        Ok(())
+    }
+
+    // 切换纯文本 / 正则匹配，已有搜索按新方式重新执行
+    pub fn set_search_regex(&mut self, regex: bool) -> Result<(), String> {
+        self.search_regex = regex;
+        match &self.search {
+            Some(search) => {
+                let query = search.pattern.as_str().to_string();
+                self.set_search(&query)
+            }
+            None => Ok(()),
+        }
+    }
+
+    // 跳到下一个匹配，到末尾后回到第一个
+    pub fn search_next(&mut self) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        let next = match search.current {
+            Some(current) => search.matches.iter().find(|&&m| m > current),
+            None => None,
+        }
+        .or(search.matches.first())
+        .copied();
+        search.current = next;
+        if let Some(line) = next {
+            self.scroll_to_line(line);
+        }
+    }
+
+    // 跳到上一个匹配，到开头后回到最后一个
+    pub fn search_prev(&mut self) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        let prev = match search.current {
+            Some(current) => search.matches.iter().rev().find(|&&m| m < current),
+            None => None,
+        }
+        .or(search.matches.last())
+        .copied();
+        search.current = prev;
+        if let Some(line) = prev {
+            self.scroll_to_line(line);
+        }
+    }
+
+    // 只显示匹配（invert 时为不匹配）正则的行，底部行保持不变
+    pub fn set_filter(&mut self, pattern: &str, invert: bool) -> Result<(), String> {
+        self.filter = Some(Filter {
+            pattern: Pattern::new(pattern, true)?,
+            invert,
+            lines: Vec::new(),
+        });
+        self.rescan(self.dropped);
+        Ok(())
+    }
+
+    // 恢复显示全部行，停留在过滤时的位置
+    pub fn clear_filter(&mut self) {
+        self.filter = None;
+        self.rescan(self.dropped);
+    }
+
+    // 当前的过滤条件：表达式和是否反向
+    pub fn filter(&self) -> Option<(&str, bool)> {
+        self.filter
+            .as_ref()
+            .map(|filter| (filter.pattern.as_str(), filter.invert))
+    }
+
+    pub fn set_terminal(&mut self, terminal: bool) {
+        self.terminal = terminal;
+        if terminal && self.screen.is_none() {
+            self.screen = Some(Screen::new(80, 24, self.charset));
+        }
+    }
+
+    // 终端屏幕对 DSR 等查询的应答；不在终端模式时丢弃，不替用户回话
+    pub fn take_terminal_replies(&mut self) -> Vec<u8> {
+        match &mut self.screen {
+            Some(screen) if self.terminal => screen.take_replies(),
+            Some(screen) => {
+                screen.take_replies();
+                Vec::new()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.terminal
+    }
+
+    pub fn set_focus(&mut self, focus: bool) {
+        self.is_focus = focus;
+    }
+
+    // 最后一行的全局行号
+    fn last_line(&self) -> u64 {
+        self.dropped + self.lines.len().saturating_sub(1) as u64
+    }
+
+    // 以下的“位置”指过滤后显示序列中的下标，没有过滤时与 lines 的下标相同
+    fn view_len(&self) -> usize {
+        match &self.filter {
+            Some(filter) => filter.lines.len(),
+            None => self.lines.len(),
+        }
+    }
+
+    // 位置 pos 的行在 lines 中的下标
+    fn view_index(&self, pos: usize) -> usize {
+        match &self.filter {
+            Some(filter) => (filter.lines[pos] - self.dropped) as usize,
+            None => pos,
+        }
+    }
+
+    // 位置 pos 的行的全局行号
+    fn view_line(&self, pos: usize) -> u64 {
+        self.dropped + self.view_index(pos) as u64
+    }
+
+    // 全局行号 line（被过滤掉时取它之前最近的一行）所在的位置
+    fn view_pos(&self, line: u64) -> usize {
+        let pos = match &self.filter {
+            Some(filter) => filter
+                .lines
+                .partition_point(|&l| l <= line)
+                .saturating_sub(1),
+            None => line.saturating_sub(self.dropped) as usize,
+        };
+        pos.min(self.view_len().saturating_sub(1))
+    }
+
+    // 当前显示在底部的行的位置
+    fn bottom_pos(&self) -> usize {
+        match self.bottom {
+            Some(bottom) => self.view_pos(bottom),
+            None => self.view_len().saturating_sub(1),
+        }
+    }
+
+    // 让位置 pos 的行显示在底部，到达最后一行时恢复跟随
+    fn scroll_to_pos(&mut self, pos: usize) {
+        if pos + 1 >= self.view_len() {
+            self.scroll_to_bottom();
+        } else {
+            self.bottom = Some(self.view_line(pos));
+        }
+    }
+
+    pub fn scroll_up(&mut self, n: usize) {
+        if self.view_len() > 0 {
+            self.bottom = Some(self.view_line(self.bottom_pos().saturating_sub(n)));
+        }
+    }
+
+    pub fn scroll_down(&mut self, n: usize) {
+        if self.bottom.is_some() {
+            self.scroll_to_pos(self.bottom_pos() + n);
+        }
+    }
+
+    pub fn page_up(&mut self) {
+        self.scroll_up(self.page.saturating_sub(1).max(1));
+    }
+
+    pub fn page_down(&mut self) {
+        self.scroll_down(self.page.saturating_sub(1).max(1));
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        // 渲染时会校正为第一页实际的最后一行
+        self.scroll_to_pos(self.page.saturating_sub(1));
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.bottom = None;
+        self.new_data = false;
+    }
+
+    // 滚动使全局行号 line 大致位于屏幕中间
+    fn scroll_to_line(&mut self, line: u64) {
+        self.scroll_to_pos(self.view_pos(line) + self.page / 2);
+    }
+
+    pub fn set_view(&mut self, view: ViewMode) {
+        self.view = view;
+    }
+
+    pub fn newline(&self) -> NewlineMode {
+        self.newline
+    }
+
+    // 只影响之后收到的数据，已有的行按原来的方式断行
+    pub fn set_newline(&mut self, newline: NewlineMode) {
+        self.newline = newline;
+        self.pending_pair = None;
+    }
+
+    pub fn charset(&self) -> Charset {
+        self.charset
+    }
+
+    // 切换字符集后按新的字符集重新过滤和搜索
+    pub fn set_charset(&mut self, charset: Charset) {
+        self.charset = charset;
+        if let Some(screen) = &mut self.screen {
+            screen.set_charset(charset);
+        }
+        self.rescan(self.dropped);
+    }
+
+    pub fn ansi(&self) -> AnsiMode {
+        self.ansi
+    }
+
+    pub fn set_ansi(&mut self, ansi: AnsiMode) {
+        self.ansi = ansi;
+    }
+
+    pub fn timestamp(&self) -> TimestampMode {
+        self.timestamp
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: TimestampMode) {
+        self.timestamp = timestamp;
+    }
+
+    pub fn set_status(&mut self, status: Option<String>) {
+        self.status = status;
+    }
+
+    pub fn rules(&self) -> &[HighlightRule] {
+        &self.rules
+    }
+
+    pub fn add_rule(&mut self, rule: HighlightRule) {
+        self.rules.push(rule);
+    }
+
+    // 删除第 n 条规则（从 1 开始），None 表示全部删除
+    pub fn remove_rule(&mut self, n: Option<usize>) -> Result<(), String> {
+        match n {
+            None => self.rules.clear(),
+            Some(n) if (1..=self.rules.len()).contains(&n) => {
+                self.rules.remove(n - 1);
+            }
+            Some(n) => return Err(format!("No highlight rule #{n}")),
+        }
+        Ok(())
+    }
+
+    // 行首时间戳，prev 为上一行的时间（用于计算差值）
+    fn timestamp_span(
+        &self,
+        time: DateTime<Local>,
+        prev: Option<DateTime<Local>>,
+    ) -> Option<Span<'static>> {
+        let style = Style::new().fg(Color::DarkGray);
+        let text = match self.timestamp {
+            TimestampMode::Off => return None,
+            TimestampMode::Absolute => format!("[{}] ", time.format("%H:%M:%S%.3f")),
+            TimestampMode::Delta => {
+                let delta = prev.map(|p| time - p).unwrap_or_default();
+                format!("[+{:>9.3}] ", delta.num_milliseconds() as f64 / 1000.0)
+            }
+        };
+        Some(Span::styled(text, style))
+    }
+
+    // 十六进制视图：连续的接收数据按接收流中 16 字节对齐的偏移转储，不受断行影响。
+    // 每一行转储归它的第一个字节所在的数据行，内容可以延续到后面相邻的接收行；
+    // 回显 / 提示打断接收数据时，之后的数据从所在位置重新开始（行首留空保持对齐）
+    fn hex_rows(&self, index: usize) -> Vec<(usize, Line<'static>)> {
+        let line = &self.lines[index];
+        let end = line.offset + line.bytes.len();
+        let run_start = index == 0 || self.lines[index - 1].dir != Direction::Rx;
+        let mut start = if run_start {
+            line.offset
+        } else {
+            line.offset.next_multiple_of(HEX_ROW_BYTES)
+        };
+        let mut rows = Vec::new();
+        while start < end {
+            let row_end = (start / HEX_ROW_BYTES + 1) * HEX_ROW_BYTES;
+            let mut chunk = Vec::with_capacity(HEX_ROW_BYTES);
+            let mut pos = start;
+            for next in self
+                .lines
+                .range(index..)
+                .take_while(|l| l.dir == Direction::Rx)
+            {
+                let next_end = next.offset + next.bytes.len();
+                let take_end = row_end.min(next_end);
+                if pos < take_end {
+                    chunk.extend_from_slice(&next.bytes[pos - next.offset..take_end - next.offset]);
+                    pos = take_end;
+                }
+                if pos == row_end {
+                    break;
+                }
+            }
+            rows.push((start - line.offset, hex_line(&chunk, start)));
+            start = row_end;
+        }
+        rows
+    }
+
+    // 第 index 行按当前显示方式生成的屏幕行（hex 视图下一行数据可能对应多行）
+    fn render_line(&self, index: usize) -> Vec<Line<'static>> {
+        let line = &self.lines[index];
+        let mut prev = index.checked_sub(1).map(|i| self.lines[i].time());
+        // show 模式下连同换行符一起显示
+        let content = match self.newline {
+            NewlineMode::Show => &line.bytes[..],
+            _ => line.content(),
+        };
+        let rows: Vec<(usize, Line<'static>)> = match (line.dir, self.view) {
+            (Direction::Tx | Direction::Note, _) => {
+                let echo = String::from_utf8_lossy(&line.bytes).into_owned();
+                vec![(0, Line::from(Span::styled(echo, line.dir.style())))]
+            }
+            (Direction::Rx, ViewMode::Hex) => self.hex_rows(index),
+            (Direction::Rx, ViewMode::Mixed) => {
+                vec![(0, mixed_line(content, self.charset))]
+            }
+            (Direction::Rx, ViewMode::Text) => {
+                let mut row = ansi_line(content, line.style, self.ansi, self.charset);
+                for span in &mut row.spans {
+                    if let Cow::Owned(text) = self.newline.display(&span.content) {
+                        span.content = text.into();
+                    }
+                }
+                vec![(0, row)]
+            }
+        };
+        // 高亮规则只作用于文本 / 混合视图下的接收行，整行规则按整行内容匹配
+        let rules: Vec<&HighlightRule> = if line.dir == Direction::Rx && self.view != ViewMode::Hex
+        {
+            self.rules.iter().collect()
+        } else {
+            Vec::new()
+        };
+        let text = if rules.iter().any(|r| r.whole_line) {
+            line.text(self.charset)
+        } else {
+            String::new()
+        };
+        // 十六进制视图的接收数据不做搜索高亮
+        let search = self
+            .search
+            .as_ref()
+            .filter(|_| line.dir == Direction::Tx || self.view != ViewMode::Hex);
+        let is_current = search.is_some_and(|s| s.current == Some(self.dropped + index as u64));
+        let style = if is_current {
+            Style::new().fg(Color::Black).bg(Color::LightRed)
+        } else {
+            Style::new().fg(Color::Black).bg(Color::Yellow)
+        };
+        rows.into_iter()
+            .map(|(start, mut row)| {
+                for rule in &rules {
+                    if rule.whole_line {
+                        if rule.pattern.is_match(&text) {
+                            row = row.patch_style(rule.style);
+                        }
+                    } else {
+                        let ranges: Vec<_> = rule
+                            .pattern
+                            .find_iter(&line_text(&row))
+                            .filter(|m| !m.is_empty())
+                            .map(|m| m.range())
+                            .collect();
+                        row = highlight(row, &ranges, rule.style);
+                    }
+                }
+                if let Some(search) = search {
+                    let ranges = search.pattern.find_ranges(&line_text(&row));
+                    row = highlight(row, &ranges, style);
+                }
+                let time = line.time_at(start);
+                if let Some(span) = self.timestamp_span(time, prev) {
+                    row.spans.insert(0, span);
+                }
+                prev = Some(time);
+                row
+            })
+            .collect()
     }
 }
 
+// 混合模式：能按字符集解码的可打印字符原样输出，控制字符和非法字节显示为 <0xXX>
+fn mixed_line(bytes: &[u8], charset: Charset) -> Line<'static> {
+    let mut line = Line::default();
+    let mut plain = String::new();
+    if charset.is_utf8() {
+        for chunk in bytes.utf8_chunks() {
+            push_mixed_text(&mut line, &mut plain, chunk.valid());
+            push_mixed_bytes(&mut line, &mut plain, chunk.invalid());
+        }
+    } else {
+        let mut decoder = charset.decoder();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let capacity = decoder
+                .max_utf8_buffer_length_without_replacement(rest.len())
+                .unwrap_or(rest.len() * 4);
+            let mut text = String::with_capacity(capacity);
+            let (result, read) =
+                decoder.decode_to_string_without_replacement(rest, &mut text, true);
+            push_mixed_text(&mut line, &mut plain, &text);
+            if let DecoderResult::Malformed(bad, extra) = result {
+                let start = read - bad as usize - extra as usize;
+                push_mixed_bytes(&mut line, &mut plain, &rest[start..read]);
+            }
+            rest = &rest[read..];
+        }
+    }
+    if !plain.is_empty() {
+        line.push_span(Span::raw(plain));
+    }
+    line
+}
+
+fn push_mixed_text(line: &mut Line<'static>, plain: &mut String, text: &str) {
+    for ch in text.chars() {
+        if ch.is_control() {
+            let mut buf = [0u8; 4];
+            push_mixed_bytes(line, plain, ch.encode_utf8(&mut buf).as_bytes());
+        } else {
+            plain.push(ch);
+        }
+    }
+}
+
+fn push_mixed_bytes(line: &mut Line<'static>, plain: &mut String, bytes: &[u8]) {
+    if bytes.is_empty() {
+        return;
+    }
+    if !plain.is_empty() {
+        line.push_span(Span::raw(std::mem::take(plain)));
+    }
+    for b in bytes {
+        line.push_span(Span::styled(
+            format!("<0x{b:02X}>"),
+            Style::new().fg(Color::Yellow),
+        ));
+    }
+}
+
+// 十六进制模式：xxd 风格的一行，offset 为接收流中的全局偏移
+// offset 不是 16 的倍数时显示所在行的对齐偏移，左侧留空
+fn hex_line(chunk: &[u8], offset: usize) -> Line<'static> {
+    let lead = offset % HEX_ROW_BYTES;
+    let (hex, ascii) = padded_hex_row(lead, chunk);
+    Line::from(vec![
+        Span::styled(
+            format!("{:08x}: ", offset - lead),
+            Style::new().fg(Color::DarkGray),
+        ),
+        Span::raw(format!("{hex:<39}  ")),
+        Span::styled(ascii, Style::new().fg(Color::Cyan)),
+    ])
+}
+
+// 一行（最多 16 字节）的十六进制栏和 ASCII 栏，日志的 hex 格式也用它
+pub fn hex_row(chunk: &[u8]) -> (String, String) {
+    padded_hex_row(0, chunk)
+}
+
+// 同 hex_row，但数据从第 lead 列开始，前面的列留空
+fn padded_hex_row(lead: usize, chunk: &[u8]) -> (String, String) {
+    let mut hex = String::with_capacity(HEX_ROW_BYTES * 5 / 2);
+    let mut ascii = String::with_capacity(HEX_ROW_BYTES);
+    let cells = std::iter::repeat_n(None, lead).chain(chunk.iter().map(Some));
+    for (i, b) in cells.enumerate() {
+        if i > 0 && i % 2 == 0 {
+            hex.push(' ');
+        }
+        match b {
+            Some(b) => {
+                hex.push_str(&format!("{b:02x}"));
+                ascii.push(if b.is_ascii_graphic() || *b == b' ' {
+                    *b as char
+                } else {
+                    '.'
+                });
+            }
+            None => {
+                hex.push_str("  ");
+                ascii.push(' ');
+            }
+        }
+    }
+    (hex, ascii)
+}
+
 pub struct ReceiveText;
 impl StatefulWidget for ReceiveText {
     type State = ReceiveTextState;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let block = Block::bordered()
-            .border_style(Style::new().fg(Color::Gray))
-            .title("接收区");
-        let mut p = Paragraph::new(state.content.clone())
-            .wrap(Wrap { trim: true })
-            .block(block);
-        let len = p.line_count(area.width - 2) as u16;
-        let height = area.height + state.scroll;
+        if state.terminal
+            && let Some(screen) = &mut state.screen
+        {
+            let inner = Block::bordered().inner(area);
+            screen.resize(inner.width as usize, inner.height as usize);
+            let (cols, rows) = screen.size();
+            let block = Block::bordered()
+                .border_style(Style::new().fg(Color::LightGreen))
+                .title(format!(
+                    "终端 {cols}x{rows} (Ctrl-A x 退出, Ctrl-A a 发送 Ctrl-A)"
+                ));
+            let block = match &state.status {
+                Some(status) => block.title(
+                    Line::styled(status.clone(), Style::new().fg(Color::LightRed)).right_aligned(),
+                ),
+                None => block,
+            };
+            block.render(area, buf);
+            screen.render(inner, buf, state.is_focus);
+            return;
+        }
+        let mut label = state.view.label().to_string();
+        if state.view == ViewMode::Text && state.ansi != AnsiMode::default() {
+            label = format!("{label} {}", state.ansi.label());
+        }
+        let mut title = vec![Span::raw(format!("接收区 [{label}]"))];
+        if state.dropped > 0 {
+            title.push(Span::styled(
+                format!(" 已丢弃 {} 行", state.dropped),
+                Style::new().fg(Color::Yellow),
+            ));
+        }
+        if let Some(search) = &state.search {
+            let position = search
+                .current
+                .and_then(|c| search.matches.iter().position(|&m| m == c))
+                .map_or(0, |i| i + 1);
+            title.push(Span::styled(
+                format!(
+                    " 搜索 \"{}\" {}/{}",
+                    search.pattern.as_str(),
+                    position,
+                    search.matches.len()
+                ),
+                Style::new().fg(Color::LightCyan),
+            ));
+        }
+        if let Some(filter) = &state.filter {
+            title.push(Span::styled(
+                format!(
+                    " 过滤{} \"{}\" {} 行",
+                    if filter.invert { " -v" } else { "" },
+                    filter.pattern.as_str(),
+                    filter.lines.len()
+                ),
+                Style::new().fg(Color::LightMagenta),
+            ));
+        }
+        let border = if state.is_focus {
+            Color::LightYellow
+        } else {
+            Color::Gray
+        };
+        let mut block = Block::bordered()
+            .border_style(Style::new().fg(border))
+            .title(Line::from(title));
+        // 暂停跟随时在底边提示，有新数据时更醒目
+        if state.bottom.is_some() {
+            let badge = if state.new_data {
+                Line::styled(
+                    " ▼ 有新数据 (End 跳到底部) ",
+                    Style::new().fg(Color::Black).bg(Color::LightYellow),
+                )
+            } else {
+                Line::styled(" 已暂停跟随 (End 恢复) ", Style::new().fg(Color::DarkGray))
+            };
+            block = block.title_bottom(badge.right_aligned());
+        }
+        if let Some(status) = &state.status {
+            block = block.title(
+                Line::styled(status.clone(), Style::new().fg(Color::LightRed)).right_aligned(),
+            );
+        }
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // 从底部那一行往前，只为能填满可见区域的行生成内容
+        // 十六进制模式按固定列宽排版，不自动换行
+        let wrap = state.view != ViewMode::Hex;
+        let height = inner.height as usize;
+        let line_rows = |lines: &Vec<Line>| {
+            if wrap {
+                Paragraph::new(lines.clone())
+                    .wrap(Wrap { trim: true })
+                    .line_count(inner.width)
+            } else {
+                lines.len()
+            }
+        };
+        let mut visible = Vec::new();
+        let mut rows = 0;
+        let len = state.view_len();
+        let mut bottom = state.bottom_pos();
+        let mut first = bottom + 1;
+        while first > 0 && rows < height && len > 0 {
+            first -= 1;
+            let lines = state.render_line(state.view_index(first));
+            rows += line_rows(&lines);
+            visible.insert(0, lines);
+        }
+        // 往前已经没有更多行（滚动到了顶部），用后面的行把屏幕填满
+        while rows < height && bottom + 1 < len {
+            bottom += 1;
+            let lines = state.render_line(state.view_index(bottom));
+            rows += line_rows(&lines);
+            visible.push(lines);
+        }
+        if state.bottom.is_some() && len > 0 {
+            state.bottom = Some(state.view_line(bottom));
+        }
+        state.page = height.max(1);
+        let shown = visible.len();
+        let text: Vec<Line> = visible.into_iter().flatten().collect();
 
-        if len != height {
-            state.scroll = len.saturating_sub(height);
+        // 底部对齐，最上面那一行可能只显示下半部分；滚动到顶部时则顶部对齐
+        let scroll = if first == 0 && state.bottom.is_some() {
+            0
+        } else {
+            rows.saturating_sub(height)
+        };
+        let mut p = Paragraph::new(text).scroll((scroll as u16, 0));
+        if wrap {
+            p = p.wrap(Wrap { trim: true });
         }
-        p = p.scroll((state.scroll, 0));
+        p.render(inner, buf);
 
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
             .begin_symbol(Some("↑"))
             .end_symbol(Some("↓"));
 
-        let mut scrollbar_state = ScrollbarState::new((len).saturating_sub(area.height).into())
-            .position(state.scroll.into());
-        // println!("{len} {}", state.scroll);
-        p.render(area, buf);
+        let mut scrollbar_state = ScrollbarState::new(len)
+            .position(first)
+            .viewport_content_length(shown);
         scrollbar.render(area, buf, &mut scrollbar_state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(state: &mut ReceiveTextState, data: &[u8]) {
+        state.append_bytes(data, Local::now());
+    }
+
+    fn lines(state: &ReceiveTextState) -> Vec<String> {
+        state
+            .lines
+            .iter()
+            .map(|l| String::from_utf8_lossy(&l.bytes).into_owned())
+            .collect()
+    }
+
+    fn hex_text(state: &ReceiveTextState, index: usize) -> Vec<(usize, String)> {
+        state
+            .hex_rows(index)
+            .into_iter()
+            .map(|(offset, line)| (offset, line.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn ring_buffer_drops_oldest_lines() {
+        let mut state = ReceiveTextState::default();
+        state.set_max_lines(3);
+        feed(&mut state, b"a\nb\nc\nd\ne\n");
+        assert_eq!(lines(&state), ["c\n", "d\n", "e\n"]);
+        assert_eq!(state.dropped, 2);
+        assert_eq!(state.lines[0].offset, 4);
+        assert_eq!(state.rx_total, 10);
+
+        state.set_max_lines(0);
+        assert_eq!(lines(&state), ["e\n"]);
+        assert_eq!(state.dropped, 4);
+    }
+
+    #[test]
+    fn long_lines_are_cut() {
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, &[b'x'; MAX_LINE_BYTES + 10]);
+        feed(&mut state, b"yz\n");
+        let lens: Vec<usize> = state.lines.iter().map(|l| l.bytes.len()).collect();
+        assert_eq!(lens, [MAX_LINE_BYTES, 13]);
+        assert_eq!(state.lines[1].offset, MAX_LINE_BYTES);
+    }
+
+    #[test]
+    fn scroll_position_survives_eviction() {
+        let mut state = ReceiveTextState::default();
+        state.set_max_lines(3);
+        feed(&mut state, b"a\nb\nc\n");
+        state.scroll_up(1);
+        assert_eq!(state.bottom, Some(1));
+        feed(&mut state, b"d\ne\nf\n");
+        // 底部行已被丢弃，停在现存最旧的一行
+        assert_eq!(state.dropped, 3);
+        assert_eq!(state.bottom_pos(), 0);
+        assert_eq!(state.view_line(state.bottom_pos()), 3);
+        assert!(state.new_data);
+        state.scroll_down(1);
+        assert_eq!(state.bottom, Some(4));
+        state.scroll_down(5);
+        assert_eq!(state.bottom, None);
+        assert!(!state.new_data);
+    }
+
+    #[test]
+    fn hex_rows_follow_the_rx_stream() {
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, b"abc\ndefghijklmnopqrstuvw");
+        // 第一行的转储延续到下一行，下一行从对齐的偏移 0x10 开始
+        let first = hex_text(&state, 0);
+        assert_eq!(first.len(), 1);
+        assert!(first[0].1.starts_with("00000000: 6162 630a 6465"));
+        assert!(first[0].1.ends_with("abc.defghijklmno"));
+        let second = hex_text(&state, 1);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].0, 12);
+        assert!(second[0].1.starts_with("00000010: 7071 7273 7475 7677 "));
+        assert!(second[0].1.ends_with("pqrstuvw"));
+    }
+
+    #[test]
+    fn hex_rows_restart_after_notes() {
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, b"0123456789abcdefghij");
+        state.append_note("note");
+        feed(&mut state, b"XYZ");
+        let rows = hex_text(&state, 0);
+        let offsets: Vec<usize> = rows.iter().map(|(o, _)| *o).collect();
+        assert_eq!(offsets, [0, 16]);
+        assert!(rows[1].1.starts_with("00000010: 6768 696a "));
+        // 提示之后的数据接着流偏移 20 显示，行首留空保持对齐
+        let rows = hex_text(&state, 2);
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].1.starts_with("00000010:           5859 5a "));
+        assert!(rows[0].1.ends_with("    XYZ"));
+    }
+
+    fn current_match(state: &ReceiveTextState) -> Option<u64> {
+        state.search.as_ref().and_then(|s| s.current)
+    }
+
+    #[test]
+    fn search_wraps_around() {
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, b"foo 1\nbar\nfoo 2\nfoo 3\n");
+        state.set_search("foo").unwrap();
+        assert_eq!(state.search.as_ref().unwrap().matches, [0, 2, 3]);
+        // 跟随最新数据时从最后一个匹配开始
+        assert_eq!(current_match(&state), Some(3));
+        state.search_next();
+        assert_eq!(current_match(&state), Some(0));
+        state.search_prev();
+        assert_eq!(current_match(&state), Some(3));
+        state.search_prev();
+        assert_eq!(current_match(&state), Some(2));
+        state.set_search("").unwrap();
+        assert!(state.search.is_none());
+    }
+
+    #[test]
+    fn search_matches_follow_eviction() {
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, b"foo 1\nbar\nfoo 2\nfoo 3\n");
+        state.set_search("foo").unwrap();
+        state.search_next();
+        assert_eq!(current_match(&state), Some(0));
+        state.set_max_lines(2);
+        assert_eq!(state.search.as_ref().unwrap().matches, [2, 3]);
+        assert_eq!(current_match(&state), Some(2));
+        feed(&mut state, b"foo 4\n");
+        assert_eq!(state.search.as_ref().unwrap().matches, [3, 4]);
+        assert_eq!(current_match(&state), Some(3));
+    }
+
+    #[test]
+    fn invalid_regex_keeps_the_previous_search() {
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, b"a(b\n");
+        state.set_search("(").unwrap();
+        assert_eq!(state.search.as_ref().unwrap().matches, [0]);
+        state.set_search_regex(true).unwrap_err();
+        state.set_search("a\\(").unwrap();
+        assert_eq!(state.search.as_ref().unwrap().matches, [0]);
+        assert!(state.set_search("a(").is_err());
+        assert_eq!(state.search.as_ref().unwrap().pattern.as_str(), "a\\(");
+    }
+
+    #[test]
+    fn filtered_view_mapping() {
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, b"err 1\nok\nerr 2\nok\nerr 3\n");
+        state.set_filter("err", false).unwrap();
+        assert_eq!(state.view_len(), 3);
+        assert_eq!(state.view_index(1), 2);
+        assert_eq!(state.view_line(2), 4);
+        // 被过滤掉的行映射到它之前最近的可见行
+        assert_eq!(state.view_pos(3), 1);
+        assert_eq!(state.view_pos(0), 0);
+        assert_eq!(state.view_pos(99), 2);
+
+        state.scroll_up(1);
+        assert_eq!(state.bottom, Some(2));
+        state.set_filter("err", true).unwrap();
+        assert_eq!(state.filter.as_ref().unwrap().lines, [1, 3]);
+        assert_eq!(state.bottom_pos(), 0);
+        state.clear_filter();
+        assert_eq!(state.view_len(), 5);
+        assert_eq!(state.bottom_pos(), 2);
+    }
+
+    #[test]
+    fn filter_keeps_notes_and_follows_eviction() {
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, b"err 1\nok\nerr 2\nok\n");
+        state.set_filter("err", false).unwrap();
+        state.append_note("note");
+        feed(&mut state, b"err 3\n");
+        assert_eq!(state.filter.as_ref().unwrap().lines, [0, 2, 4, 5]);
+        state.set_max_lines(3);
+        assert_eq!(state.filter.as_ref().unwrap().lines, [4, 5]);
+        assert_eq!(state.view_index(0), 1);
+        assert_eq!(state.view_pos(2), 0);
+    }
+
+    #[test]
+    fn find_break_modes() {
+        use NewlineMode::*;
+        assert_eq!(Auto.find_break(b"", b"ab\r\ncd"), Some((4, 2)));
+        assert_eq!(Auto.find_break(b"", b"ab\n\rcd"), Some((4, 2)));
+        assert_eq!(Auto.find_break(b"", b"\r\r"), Some((1, 1)));
+        assert_eq!(Auto.find_break(b"", b"ab"), None);
+        assert_eq!(Cr.find_break(b"", b"a\nb\r"), Some((4, 1)));
+        assert_eq!(Lf.find_break(b"", b"a\rb\n"), Some((4, 1)));
+        assert_eq!(Show.find_break(b"", b"a\r\n"), Some((3, 1)));
+        assert_eq!(CrLf.find_break(b"", b"a\rb\n\r\n"), Some((6, 2)));
+        assert_eq!(CrLf.find_break(b"a\r", b"\nb"), Some((1, 2)));
+        assert_eq!(CrLf.find_break(b"a", b"\nb"), None);
+    }
+
+    #[test]
+    fn pair_split_across_chunks() {
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, b"a\r");
+        feed(&mut state, b"\nb\n");
+        assert_eq!(lines(&state), ["a\r\n", "b\n"]);
+        assert_eq!(state.lines[0].eol, 2);
+
+        // 两个 CR 是两次换行
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, b"a\r");
+        feed(&mut state, b"\rb");
+        assert_eq!(lines(&state), ["a\r", "\r", "b"]);
+
+        // 中间插入提示后不再合并
+        let mut state = ReceiveTextState::default();
+        feed(&mut state, b"a\r");
+        state.append_note("note");
+        feed(&mut state, b"\n");
+        assert_eq!(lines(&state), ["a\r", "note", "\n"]);
+
+        let mut state = ReceiveTextState::default();
+        state.set_newline(NewlineMode::CrLf);
+        feed(&mut state, b"a\r");
+        feed(&mut state, b"\nb\r");
+        assert_eq!(lines(&state), ["a\r\n", "b\r"]);
+    }
+
+    #[test]
+    fn pair_split_at_line_limit() {
+        let mut state = ReceiveTextState::default();
+        let mut data = vec![b'x'; MAX_LINE_BYTES - 1];
+        data.extend_from_slice(b"\r\nnext");
+        feed(&mut state, &data);
+        let lens: Vec<(usize, usize)> =
+            state.lines.iter().map(|l| (l.bytes.len(), l.eol)).collect();
+        assert_eq!(lens, [(MAX_LINE_BYTES + 1, 2), (4, 0)]);
+        assert_eq!(state.rx_total, MAX_LINE_BYTES + 5);
+    }
+}
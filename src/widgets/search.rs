@@ -0,0 +1,95 @@
+use std::ops::Range;
+
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use regex::Regex;
+
+// 匹配模式：纯文本或正则
+#[derive(Clone)]
+pub enum Pattern {
+    Plain(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn new(query: &str, regex: bool) -> Result<Self, String> {
+        if regex {
+            Regex::new(query)
+                .map(Pattern::Regex)
+                .map_err(|e| format!("Invalid regex \"{query}\": {e}"))
+        } else {
+            Ok(Pattern::Plain(query.to_string()))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Pattern::Plain(s) => s,
+            Pattern::Regex(re) => re.as_str(),
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        match self {
+            Pattern::Plain(s) => text.contains(s.as_str()),
+            Pattern::Regex(re) => re.is_match(text),
+        }
+    }
+
+    // 所有非空匹配的字节范围，按位置升序且互不重叠
+    pub fn find_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        match self {
+            Pattern::Plain(s) if s.is_empty() => Vec::new(),
+            Pattern::Plain(s) => text
+                .match_indices(s.as_str())
+                .map(|(i, m)| i..i + m.len())
+                .collect(),
+            Pattern::Regex(re) => re
+                .find_iter(text)
+                .filter(|m| !m.is_empty())
+                .map(|m| m.range())
+                .collect(),
+        }
+    }
+}
+
+// 一行中所有 span 拼起来的文本
+pub fn line_text(line: &Line) -> String {
+    line.spans.iter().map(|s| s.content.as_ref()).collect()
+}
+
+// 在 ranges（按 line_text 的字节偏移）覆盖的部分叠加 style，必要时拆分 span
+pub fn highlight(line: Line<'static>, ranges: &[Range<usize>], style: Style) -> Line<'static> {
+    if ranges.is_empty() {
+        return line;
+    }
+    let mut spans = Vec::with_capacity(line.spans.len() + ranges.len() * 2);
+    let mut offset = 0;
+    for span in &line.spans {
+        let text = span.content.as_ref();
+        let end = offset + text.len();
+        let mut pos = offset;
+        for range in ranges.iter().filter(|r| r.start < end && r.end > offset) {
+            let start = range.start.max(offset);
+            let stop = range.end.min(end);
+            if start > pos {
+                spans.push(Span::styled(
+                    text[pos - offset..start - offset].to_string(),
+                    span.style,
+                ));
+            }
+            spans.push(Span::styled(
+                text[start - offset..stop - offset].to_string(),
+                span.style.patch(style),
+            ));
+            pos = stop;
+        }
+        if pos < end {
+            spans.push(Span::styled(text[pos - offset..].to_string(), span.style));
+        }
+        offset = end;
+    }
+    Line { spans, ..line }
+}
@@ -23,9 +23,19 @@ impl SelectableListState {
         }
     }
 
-    // 更新列表（比如刷新串口）
+    // 更新列表（比如刷新串口），原选中项还在时保持选中，否则选中同一位置
     pub fn update_items(&mut self, new_items: Vec<String>) {
+        let selected = self.selected_item().map(str::to_string);
+        let index = self.selected().unwrap_or(0);
         self.items = new_items;
+        if self.items.is_empty() {
+            self.state.select(None);
+            return;
+        }
+        self.state.select(Some(index.min(self.items.len() - 1)));
+        if let Some(item) = selected {
+            self.select_item(&item);
+        }
     }
 
     // 处理向下
@@ -48,6 +58,13 @@ impl SelectableListState {
         self.state.select(Some(next));
     }
 
+    // 按值选中某一项（找不到则保持不变）
+    pub fn select_item(&mut self, item: &str) {
+        if let Some(i) = self.items.iter().position(|s| s == item) {
+            self.state.select(Some(i));
+        }
+    }
+
     // 获取当前选中的索引
     pub fn selected(&self) -> Option<usize> {
         self.state.selected()
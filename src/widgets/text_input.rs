@@ -8,9 +8,11 @@ pub struct TextInputState {
 }
 
 impl TextInputState {
-    pub fn handle_key(&mut self, key: crossterm::event::KeyEvent) -> String {
+    // 按下 Enter 时返回整行内容（可能为空串），其余按键返回 None
+    #[allow(clippy::collapsible_match)]
+    pub fn handle_key(&mut self, key: crossterm::event::KeyEvent) -> Option<String> {
         if key.kind != crossterm::event::KeyEventKind::Press {
-            return String::new();
+            return None;
         }
 
         match key.code {
@@ -71,53 +73,51 @@ impl TextInputState {
             }
             crossterm::event::KeyCode::Home => self.cursor = 0,
             crossterm::event::KeyCode::End => self.cursor = self.content.chars().count(),
-            crossterm::event::KeyCode::Esc => return String::new(),
+            crossterm::event::KeyCode::Esc => return None,
             crossterm::event::KeyCode::Enter => {
                 let s = self.content.clone();
                 self.content.clear();
                 self.cursor = 0;
-                return s;
+                return Some(s);
             }
             _ => {}
         }
-        String::new()
+        None
     }
 
     pub fn value(&self) -> &str {
         &self.content
     }
 
-    pub fn set_value(&mut self, s: String) {
-        self.content = s;
-        self.cursor = self.content.chars().count(); // ✅ 字符数，不是字节数
-    }
-
-    pub fn visual_cursor(&self) -> usize {
-        self.cursor // 字符位置，正确
-    }
-
     pub fn set_focus(&mut self, focus: bool) {
         self.is_focus = focus;
     }
 }
 
-pub struct TextInput;
+pub struct TextInput {
+    title: String,
+}
+impl TextInput {
+    pub fn new(title: String) -> Self {
+        Self { title }
+    }
+}
 
 impl StatefulWidget for TextInput {
     type State = TextInputState;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        use ratatui::style::{Modifier, Style};
+        use ratatui::style::Style;
         use ratatui::text::{Line, Span};
 
         let block = if state.is_focus {
             Block::bordered()
                 .border_style(Style::new().fg(Color::LightYellow))
-                .title("Input")
+                .title(self.title)
         } else {
             Block::bordered()
                 .border_style(Style::new().fg(Color::Gray))
-                .title("Input")
+                .title(self.title)
         };
 
         let text_line = if state.is_focus {
@@ -0,0 +1,565 @@
+use encoding_rs::Decoder;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+};
+use unicode_width::UnicodeWidthChar;
+
+use super::ansi::apply_sgr;
+use crate::encoding::{Charset, decode_stream};
+
+// CSI 数值参数的上限
+const MAX_PARAM: usize = u16::MAX as usize;
+// CSI 参数部分的最大长度，超出时忽略整个序列，防止不带结束字节的序列无限占用内存
+const MAX_PARAMS_LEN: usize = 64;
+
+// 屏幕上的一个字符格，宽字符的右半格 ch 为 '\0'
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    ch: char,
+    style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            style: Style::new(),
+        }
+    }
+}
+
+// 转义序列解析状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    EscIntermediate, // ESC ( B 这类三字节序列
+    Csi,
+    CsiIgnore, // 参数过长的 CSI 序列，丢弃到结束字节为止
+    Osc,       // OSC / DCS 等字符串序列，内容忽略
+    OscEscape, // 字符串序列中遇到 ESC，等待 '\'
+}
+
+// 终端模式下接收区使用的最小 VT100 屏幕：
+// 支持光标移动、擦除、插入删除行和字符、滚动区域和 SGR 样式，其余序列忽略
+pub struct Screen {
+    cells: Vec<Vec<Cell>>,
+    cols: usize,
+    rows: usize,
+    row: usize,
+    col: usize, // 等于 cols 表示已写到行尾，下一个字符前先换行
+    style: Style,
+    saved: (usize, usize, Style),
+    top: usize, // 滚动区域（含两端）
+    bottom: usize,
+    cursor_visible: bool,
+    state: State,
+    params: Vec<u8>,  // CSI 参数和中间字节
+    replies: Vec<u8>, // 需要回给对端的应答，如 DSR 查询的结果
+    charset: Charset,
+    decoder: Decoder, // 先按字符集解码再解析转义序列，多字节字符可以跨读取块
+}
+
+impl Screen {
+    pub fn new(cols: usize, rows: usize, charset: Charset) -> Self {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        Self {
+            cells: vec![vec![Cell::default(); cols]; rows],
+            cols,
+            rows,
+            row: 0,
+            col: 0,
+            style: Style::new(),
+            saved: (0, 0, Style::new()),
+            top: 0,
+            bottom: rows - 1,
+            cursor_visible: true,
+            state: State::Ground,
+            params: Vec::new(),
+            replies: Vec::new(),
+            charset,
+            decoder: charset.decoder(),
+        }
+    }
+
+    pub fn set_charset(&mut self, charset: Charset) {
+        self.charset = charset;
+        self.decoder = charset.decoder();
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.cols, self.rows)
+    }
+
+    // 调整大小时保留左上角的内容；行数变少时丢弃顶部的行，保证光标仍然可见
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        if (cols, rows) == (self.cols, self.rows) {
+            return;
+        }
+        if self.row >= rows {
+            self.cells.drain(..self.row + 1 - rows);
+            self.row = rows - 1;
+        }
+        self.cells.resize(rows, vec![Cell::default(); cols]);
+        for line in &mut self.cells {
+            line.resize(cols, Cell::default());
+        }
+        self.cols = cols;
+        self.rows = rows;
+        self.col = self.col.min(cols);
+        self.top = 0;
+        self.bottom = rows - 1;
+        self.saved.0 = self.saved.0.min(rows - 1);
+        self.saved.1 = self.saved.1.min(cols - 1);
+    }
+
+    // 取走 feed 过程中产生的应答，由调用方写回串口
+    pub fn take_replies(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.replies)
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for ch in decode_stream(&mut self.decoder, bytes).chars() {
+            self.char(ch);
+        }
+    }
+
+    fn char(&mut self, ch: char) {
+        // 转义序列只由 ASCII 组成，序列中出现其他字符时放弃该序列
+        let b = if ch.is_ascii() { ch as u8 } else { 0xFF };
+        match self.state {
+            State::Ground => match ch {
+                '\x1b' => self.state = State::Escape,
+                '\0'..='\x1f' | '\x7f' => self.control(b),
+                '\u{80}'..='\u{9f}' => {} // C1 控制字符忽略
+                _ => self.print(ch),
+            },
+            State::Escape => self.escape(b),
+            State::EscIntermediate => {
+                if !(0x20..=0x2F).contains(&b) {
+                    self.state = State::Ground;
+                }
+            }
+            State::Csi => match b {
+                0x1B => self.state = State::Escape,
+                0x00..=0x1F => self.control(b),
+                0x20..=0x3F if self.params.len() < MAX_PARAMS_LEN => self.params.push(b),
+                0x20..=0x3F => self.state = State::CsiIgnore,
+                0x40..=0x7E => {
+                    self.state = State::Ground;
+                    self.csi(b);
+                }
+                _ => self.state = State::Ground,
+            },
+            State::CsiIgnore => match b {
+                0x1B => self.state = State::Escape,
+                0x00..=0x1F => self.control(b),
+                0x20..=0x3F => {}
+                _ => self.state = State::Ground,
+            },
+            State::Osc => match b {
+                0x07 => self.state = State::Ground,
+                0x1B => self.state = State::OscEscape,
+                _ => {}
+            },
+            State::OscEscape => self.state = State::Ground,
+        }
+    }
+
+    fn control(&mut self, b: u8) {
+        match b {
+            b'\r' => self.col = 0,
+            b'\n' | 0x0B | 0x0C => self.line_feed(),
+            0x08 => self.col = self.cursor_col().saturating_sub(1),
+            b'\t' => self.col = ((self.cursor_col() / 8 + 1) * 8).min(self.cols - 1),
+            _ => {} // BEL 等其余控制字符忽略
+        }
+    }
+
+    fn escape(&mut self, b: u8) {
+        self.state = State::Ground;
+        match b {
+            b'[' => {
+                self.params.clear();
+                self.state = State::Csi;
+            }
+            b']' | b'P' | b'X' | b'^' | b'_' => self.state = State::Osc,
+            0x20..=0x2F => self.state = State::EscIntermediate,
+            b'7' => self.saved = (self.row, self.cursor_col(), self.style),
+            b'8' => (self.row, self.col, self.style) = self.saved,
+            b'D' => self.line_feed(),
+            b'E' => {
+                self.col = 0;
+                self.line_feed();
+            }
+            b'M' => self.reverse_index(),
+            b'c' => *self = Screen::new(self.cols, self.rows, self.charset),
+            _ => {}
+        }
+    }
+
+    fn csi(&mut self, action: u8) {
+        let private = self.params.first() == Some(&b'?');
+        let params = String::from_utf8_lossy(&self.params).into_owned();
+        // 参数来自对端设备，超大的数值截断到 u16::MAX，后面的加法不会溢出
+        let nums: Vec<usize> = params
+            .trim_start_matches('?')
+            .split(';')
+            .map(|p| {
+                let digits = !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit());
+                match p.parse::<usize>() {
+                    Ok(v) => v.min(MAX_PARAM),
+                    Err(_) if digits => MAX_PARAM,
+                    Err(_) => 0,
+                }
+            })
+            .collect();
+        // 缺省或为 0 的参数按 1 处理
+        let n = |i: usize| nums.get(i).copied().filter(|&v| v > 0).unwrap_or(1);
+        let col = self.cursor_col();
+        match action {
+            b'A' => self.row = self.row.saturating_sub(n(0)),
+            b'B' | b'e' => self.row = self.row.saturating_add(n(0)).min(self.rows - 1),
+            b'C' | b'a' => self.col = col.saturating_add(n(0)).min(self.cols - 1),
+            b'D' => self.col = col.saturating_sub(n(0)),
+            b'E' => {
+                self.row = self.row.saturating_add(n(0)).min(self.rows - 1);
+                self.col = 0;
+            }
+            b'F' => {
+                self.row = self.row.saturating_sub(n(0));
+                self.col = 0;
+            }
+            b'G' | b'`' => self.col = (n(0) - 1).min(self.cols - 1),
+            b'd' => self.row = (n(0) - 1).min(self.rows - 1),
+            b'H' | b'f' => {
+                self.row = (n(0) - 1).min(self.rows - 1);
+                self.col = (n(1) - 1).min(self.cols - 1);
+            }
+            b'J' => self.erase_display(nums[0]),
+            b'K' => self.erase_line(nums[0]),
+            b'L' if (self.top..=self.bottom).contains(&self.row) => {
+                for _ in 0..n(0).min(self.bottom - self.row + 1) {
+                    self.cells.remove(self.bottom);
+                    self.cells.insert(self.row, self.blank_line());
+                }
+            }
+            b'M' if (self.top..=self.bottom).contains(&self.row) => {
+                for _ in 0..n(0).min(self.bottom - self.row + 1) {
+                    self.cells.remove(self.row);
+                    self.cells.insert(self.bottom, self.blank_line());
+                }
+            }
+            b'P' => {
+                let line = &mut self.cells[self.row];
+                line.drain(col..col.saturating_add(n(0)).min(self.cols));
+                line.resize(self.cols, Cell::default());
+            }
+            b'@' => {
+                let line = &mut self.cells[self.row];
+                for _ in 0..n(0).min(self.cols - col) {
+                    line.insert(col, Cell::default());
+                }
+                line.truncate(self.cols);
+            }
+            b'X' => {
+                let end = col.saturating_add(n(0)).min(self.cols);
+                self.cells[self.row][col..end].fill(Cell::default());
+            }
+            b'S' => self.scroll_up(n(0)),
+            b'T' => self.scroll_down(n(0)),
+            b'm' if !private => self.style = apply_sgr(self.style, &self.params),
+            b'r' if !private => {
+                let top = n(0) - 1;
+                let bottom = nums.get(1).copied().filter(|&v| v > 0).unwrap_or(self.rows) - 1;
+                if top < bottom && bottom < self.rows {
+                    self.top = top;
+                    self.bottom = bottom;
+                    self.row = 0;
+                    self.col = 0;
+                }
+            }
+            b's' => self.saved = (self.row, col, self.style),
+            b'u' => (self.row, self.col, self.style) = self.saved,
+            b'h' | b'l' if private && nums.contains(&25) => self.cursor_visible = action == b'h',
+            // DSR：5 查询状态，6 查询光标位置（从 1 开始）
+            b'n' if !private && nums[0] == 5 => self.replies.extend_from_slice(b"\x1b[0n"),
+            b'n' if !private && nums[0] == 6 => {
+                let reply = format!("\x1b[{};{}R", self.row + 1, col + 1);
+                self.replies.extend_from_slice(reply.as_bytes());
+            }
+            _ => {}
+        }
+    }
+
+    fn print(&mut self, ch: char) {
+        let width = ch.width().unwrap_or(0);
+        // 组合字符等零宽字符直接忽略，宽字符放不下整个屏幕时也忽略
+        if width == 0 || width > self.cols {
+            return;
+        }
+        if self.col + width > self.cols {
+            self.col = 0;
+            self.line_feed();
+        }
+        let (row, col) = (self.row, self.col);
+        self.clear_wide(row, col);
+        self.cells[row][col] = Cell {
+            ch,
+            style: self.style,
+        };
+        if width == 2 {
+            self.clear_wide(row, col + 1);
+            self.cells[row][col + 1] = Cell {
+                ch: '\0',
+                style: self.style,
+            };
+        }
+        self.col += width;
+    }
+
+    // 覆盖宽字符的一半时，把另一半也清掉
+    fn clear_wide(&mut self, row: usize, col: usize) {
+        let line = &mut self.cells[row];
+        if line[col].ch == '\0' && col > 0 {
+            line[col - 1] = Cell::default();
+        }
+        if col + 1 < self.cols && line[col + 1].ch == '\0' {
+            line[col + 1] = Cell::default();
+        }
+    }
+
+    fn cursor_col(&self) -> usize {
+        self.col.min(self.cols - 1)
+    }
+
+    fn blank_line(&self) -> Vec<Cell> {
+        vec![Cell::default(); self.cols]
+    }
+
+    fn line_feed(&mut self) {
+        if self.row == self.bottom {
+            self.scroll_up(1);
+        } else if self.row + 1 < self.rows {
+            self.row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        if self.row == self.top {
+            self.scroll_down(1);
+        } else {
+            self.row = self.row.saturating_sub(1);
+        }
+    }
+
+    // 滚动区域内的内容上移 n 行
+    fn scroll_up(&mut self, n: usize) {
+        for _ in 0..n.min(self.bottom - self.top + 1) {
+            self.cells.remove(self.top);
+            self.cells.insert(self.bottom, self.blank_line());
+        }
+    }
+
+    fn scroll_down(&mut self, n: usize) {
+        for _ in 0..n.min(self.bottom - self.top + 1) {
+            self.cells.remove(self.bottom);
+            self.cells.insert(self.top, self.blank_line());
+        }
+    }
+
+    // 0：光标到屏幕末尾，1：屏幕开头到光标，2 / 3：整个屏幕
+    fn erase_display(&mut self, mode: usize) {
+        match mode {
+            0 => {
+                self.erase_line(0);
+                for line in &mut self.cells[self.row + 1..] {
+                    line.fill(Cell::default());
+                }
+            }
+            1 => {
+                self.erase_line(1);
+                for line in &mut self.cells[..self.row] {
+                    line.fill(Cell::default());
+                }
+            }
+            _ => {
+                for line in &mut self.cells {
+                    line.fill(Cell::default());
+                }
+            }
+        }
+    }
+
+    // 0：光标到行尾，1：行首到光标，2：整行
+    fn erase_line(&mut self, mode: usize) {
+        let col = self.cursor_col();
+        let line = &mut self.cells[self.row];
+        match mode {
+            0 => line[col..].fill(Cell::default()),
+            1 => line[..=col].fill(Cell::default()),
+            _ => line.fill(Cell::default()),
+        }
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer, show_cursor: bool) {
+        for (y, line) in self.cells.iter().enumerate().take(area.height as usize) {
+            for (x, cell) in line.iter().enumerate().take(area.width as usize) {
+                if cell.ch == '\0' {
+                    continue;
+                }
+                let mut style = cell.style;
+                if show_cursor && self.cursor_visible && (y, x) == (self.row, self.cursor_col()) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                buf[(area.x + x as u16, area.y + y as u16)]
+                    .set_char(cell.ch)
+                    .set_style(style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    fn screen(cols: usize, rows: usize, input: &str) -> Screen {
+        let mut screen = Screen::new(cols, rows, Charset::default());
+        screen.feed(input.as_bytes());
+        screen
+    }
+
+    // 一行的文字，去掉宽字符的右半格和行尾空格
+    fn text(screen: &Screen, row: usize) -> String {
+        let line: String = screen.cells[row]
+            .iter()
+            .map(|c| c.ch)
+            .filter(|&c| c != '\0')
+            .collect();
+        line.trim_end().to_string()
+    }
+
+    #[test]
+    fn print_and_wrap() {
+        let s = screen(4, 3, "abcdef\r\nxy");
+        assert_eq!(text(&s, 0), "abcd");
+        assert_eq!(text(&s, 1), "ef");
+        assert_eq!(text(&s, 2), "xy");
+        assert_eq!((s.row, s.col), (2, 2));
+    }
+
+    #[test]
+    fn wide_chars() {
+        let s = screen(4, 2, "a中文");
+        assert_eq!(text(&s, 0), "a中");
+        assert_eq!(text(&s, 1), "文");
+    }
+
+    #[test]
+    fn cursor_movement() {
+        let s = screen(10, 5, "\x1b[3;4Hx\x1b[2Ay\x1b[10Cz");
+        assert_eq!(text(&s, 2), "   x");
+        assert_eq!(text(&s, 0), "    y    z");
+        let s = screen(10, 5, "\x1b[5;5H\x1b[H");
+        assert_eq!((s.row, s.col), (0, 0));
+        let s = screen(10, 5, "\x1b[99;99H");
+        assert_eq!((s.row, s.col), (4, 9));
+    }
+
+    #[test]
+    fn erase() {
+        let s = screen(6, 2, "abcdef\x1b[1;3H\x1b[K");
+        assert_eq!(text(&s, 0), "ab");
+        let s = screen(6, 2, "abcdef\x1b[1;3H\x1b[1K");
+        assert_eq!(text(&s, 0), "   def");
+        let s = screen(6, 2, "abc\r\ndef\x1b[2J");
+        assert_eq!((text(&s, 0), text(&s, 1)), (String::new(), String::new()));
+    }
+
+    #[test]
+    fn insert_and_delete_chars() {
+        let s = screen(6, 1, "abcdef\x1b[1;2H\x1b[2P");
+        assert_eq!(text(&s, 0), "adef");
+        let s = screen(6, 1, "abcdef\x1b[1;2H\x1b[2@");
+        assert_eq!(text(&s, 0), "a  bcd");
+        let s = screen(6, 1, "abcdef\x1b[1;2H\x1b[3X");
+        assert_eq!(text(&s, 0), "a   ef");
+    }
+
+    #[test]
+    fn scroll_region() {
+        let s = screen(3, 4, "1\r\n2\r\n3\r\n4\x1b[2;3r\x1b[3;1H\n");
+        let rows: Vec<String> = (0..4).map(|r| text(&s, r)).collect();
+        assert_eq!(rows, ["1", "3", "", "4"]);
+    }
+
+    #[test]
+    fn sgr_style() {
+        let s = screen(4, 1, "\x1b[1;31ma\x1b[0mb");
+        assert_eq!(
+            s.cells[0][0].style,
+            Style::new().fg(Color::Indexed(1)).bold()
+        );
+        assert_eq!(s.cells[0][1].style, Style::new());
+    }
+
+    #[test]
+    fn huge_params_are_clamped() {
+        let huge = "18446744073709551615";
+        let s = screen(
+            6,
+            3,
+            &format!("abcdef\x1b[1;3H\x1b[{huge}P\x1b[{huge}B\x1b[{huge}C\x1b[{huge}X\x1b[{huge}@"),
+        );
+        assert_eq!(text(&s, 0), "ab");
+        assert_eq!((s.row, s.col), (2, 5));
+        let s = screen(
+            6,
+            3,
+            &format!("\x1b[{huge}0;{huge}H\x1b[{huge}E\x1b[{huge}L"),
+        );
+        assert_eq!((s.row, s.col), (2, 0));
+    }
+
+    #[test]
+    fn osc_and_unknown_sequences_are_ignored() {
+        let s = screen(10, 1, "a\x1b]0;title\x07b\x1b(Bc\x1b[?25ld");
+        assert_eq!(text(&s, 0), "abcd");
+        assert!(!s.cursor_visible);
+    }
+
+    #[test]
+    fn utf8_split_across_feeds() {
+        let mut s = Screen::new(4, 1, Charset::default());
+        let bytes = "中".as_bytes();
+        s.feed(&bytes[..1]);
+        s.feed(&bytes[1..]);
+        assert_eq!(text(&s, 0), "中");
+    }
+
+    #[test]
+    fn device_status_reports() {
+        let mut s = screen(10, 5, "\x1b[5n");
+        assert_eq!(s.take_replies(), b"\x1b[0n");
+        assert!(s.take_replies().is_empty());
+        s.feed(b"\x1b[3;7Hab\x1b[6n\x1b[?6n\x1b[7n");
+        assert_eq!(s.take_replies(), b"\x1b[3;9R");
+    }
+
+    #[test]
+    fn overlong_csi_is_ignored() {
+        let mut s = screen(10, 2, "a");
+        s.feed(b"\x1b[");
+        s.feed(&[b'1'; 100_000]);
+        assert!(s.params.len() <= MAX_PARAMS_LEN);
+        s.feed(b"Pb\x1b[1Dc");
+        assert_eq!(text(&s, 0), "ac");
+    }
+}